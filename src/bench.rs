@@ -0,0 +1,120 @@
+use std::fmt::Write;
+use std::hint;
+use std::time::{Duration, Instant};
+
+use decomposed::{Decompose, DecomposedTime};
+use fmt::{FormatError, FormatSpec};
+
+// A fixed set of formats covering clock, sub-second and total fields, so results from
+// different runs and machines compare like for like.
+pub const FORMATS: [&str; 5] = ["%H:%M:%S",
+                                "%S.%x'%y'%z",
+                                "%Y years %D days %H:%M",
+                                "%T hours, %m minutes and %f seconds",
+                                "%U days"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchResult {
+    pub iterations: u64,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+// Deterministic durations spread evenly over magnitudes from nanoseconds to centuries. The
+// same seed always yields the same workload.
+pub fn durations(seed: u64, count: usize) -> Vec<DecomposedTime> {
+    let mut state = seed | 1;
+    let mut next = move || {
+        // xorshift64*
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    };
+
+    (0..count)
+        .map(|_| {
+            let magnitude = next() % 20;
+            let nanos = next() as u128 % 10u128.pow(magnitude as u32 + 1);
+            DecomposedTime::builder()
+                .seconds((nanos / 1_000_000_000) as u64)
+                .nanoseconds((nanos % 1_000_000_000) as u64)
+                .build()
+                .expect("generated durations are in range")
+        })
+        .collect()
+}
+
+// Formats every duration with `spec` `rounds` times, reusing one buffer so the timing covers
+// the formatter rather than the allocator.
+pub fn measure<D>(spec: FormatSpec,
+                  durations: &[D],
+                  rounds: u32)
+                  -> Result<BenchResult, FormatError>
+    where D: Decompose + Clone
+{
+    let mut buffer = String::new();
+    let mut bytes = 0;
+    let start = Instant::now();
+    for _ in 0..rounds {
+        for time in durations {
+            buffer.clear();
+            write!(buffer, "{}", spec.with_time(time.clone())?)?;
+            bytes += hint::black_box(&buffer).len() as u64;
+        }
+    }
+
+    Ok(BenchResult {
+           iterations: rounds as u64 * durations.len() as u64,
+           bytes,
+           elapsed: start.elapsed(),
+       })
+}
+
+impl BenchResult {
+    pub fn nanos_per_iteration(&self) -> f64 {
+        if self.iterations == 0 {
+            return 0.0;
+        }
+        self.elapsed.as_secs_f64() * 1.0e9 / self.iterations as f64
+    }
+    pub fn iterations_per_second(&self) -> f64 {
+        self.iterations as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_durations_deterministic() {
+        let workload = durations(7, 1000);
+        assert_eq!(workload, durations(7, 1000));
+        assert_ne!(workload, durations(8, 1000));
+        assert!(workload.iter().any(|time| time.total_seconds() == 0));
+        assert!(workload.iter().any(|time| time.years() > 0));
+    }
+
+    #[test]
+    fn test_measure() {
+        let workload = durations(1, 100);
+        for format in FORMATS.iter() {
+            let result = measure(FormatSpec::new(format).unwrap(), &workload, 3).unwrap();
+            assert_eq!(result.iterations, 300);
+            assert!(result.bytes >= 300);
+        }
+    }
+
+    // Prints each format's speed on a fixed workload. Running it with and without fast-fmt
+    // (`cargo test --release --features bench -- --ignored --nocapture`) compares the two.
+    #[test]
+    #[ignore]
+    fn bench_formats() {
+        let workload = durations(1, 10_000);
+        for format in FORMATS.iter() {
+            let result = measure(FormatSpec::new(format).unwrap(), &workload, 20).unwrap();
+            println!("{:<40} {:>8.1} ns/iter", format, result.nanos_per_iteration());
+        }
+    }
+}
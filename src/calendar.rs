@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use decomposed::{Decompose, DecomposeError, DecomposedTime};
+use fmt::FormatError;
+use units::{self, NANOS_PER_DAY, NANOS_PER_HOUR, NANOS_PER_MINUTE, NANOS_PER_SEC, NANOS_PER_YEAR};
+
+// The lengths of the day and year fields. The crate's own decomposition always uses `CIVIL`
+// (86 400 s days, 365-day years); astronomy users whose days or years are defined differently
+// decompose with `decompose_in` instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Calendar {
+    day_nanos: u64,
+    year_nanos: u64,
+}
+
+impl Calendar {
+    pub const CIVIL: Calendar = Calendar {
+        day_nanos: NANOS_PER_DAY,
+        year_nanos: NANOS_PER_YEAR,
+    };
+    // 365.25 civil days.
+    pub const JULIAN: Calendar = Calendar {
+        day_nanos: NANOS_PER_DAY,
+        year_nanos: units::SECS_PER_JULIAN_YEAR * NANOS_PER_SEC,
+    };
+    // 23h56m4.0905s days and 365.256363 civil day years.
+    pub const SIDEREAL: Calendar = Calendar {
+        day_nanos: 86_164_090_500_000,
+        year_nanos: 31_558_149_763_200_000,
+    };
+
+    // `FormatError::ValueOutOfRange` for a zero day, a year shorter than a day or a year longer
+    // than `u64::MAX` nanoseconds.
+    pub fn new(day: Duration, year: Duration) -> Result<Calendar, FormatError> {
+        if day == Duration::from_secs(0) || year < day || year.as_nanos() > u64::MAX as u128 {
+            return Err(FormatError::ValueOutOfRange);
+        }
+        Ok(Calendar {
+            day_nanos: day.as_nanos() as u64,
+            year_nanos: year.as_nanos() as u64,
+        })
+    }
+
+    pub fn day(&self) -> Duration {
+        Duration::from_nanos(self.day_nanos)
+    }
+    pub fn year(&self) -> Duration {
+        Duration::from_nanos(self.year_nanos)
+    }
+}
+
+impl Default for Calendar {
+    fn default() -> Calendar {
+        Calendar::CIVIL
+    }
+}
+
+// Decomposes `time` with the day and year lengths of `calendar`. The result doesn't remember
+// its calendar: totals and conversions read its years and days as civil ones.
+pub fn decompose_in<D>(time: D, calendar: Calendar) -> Result<DecomposedTime, DecomposeError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| e.into())?;
+    let nanos = time.total_nanoseconds().ok_or(DecomposeError::Overflow)?;
+    let year = calendar.year_nanos as u128;
+
+    let years = nanos / year;
+    if years > u64::MAX as u128 {
+        return Err(DecomposeError::Overflow);
+    }
+    let rem = (nanos % year) as u64;
+    let (days, rem) = (rem / calendar.day_nanos, rem % calendar.day_nanos);
+    if days > u32::MAX as u64 {
+        return Err(DecomposeError::Overflow);
+    }
+    let (hours, rem) = (rem / NANOS_PER_HOUR, rem % NANOS_PER_HOUR);
+    let (minutes, rem) = (rem / NANOS_PER_MINUTE, rem % NANOS_PER_MINUTE);
+    let (seconds, subsec_nanos) = (rem / NANOS_PER_SEC, (rem % NANOS_PER_SEC) as u32);
+
+    let mut decomposed = DecomposedTime::new(years as u64,
+                                             days as u32,
+                                             hours as u32,
+                                             minutes as u32,
+                                             seconds as u32,
+                                             0.0,
+                                             time.is_positive());
+    let (millis, micros, nanos) = units::split_subsec_nanos(subsec_nanos);
+    decomposed.set_milliseconds(millis);
+    decomposed.set_microseconds(micros);
+    decomposed.set_nanoseconds(nanos);
+    Ok(decomposed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompose_in() {
+        let time = Duration::from_secs(2 * 365 * 86_400);
+        let julian = decompose_in(time, Calendar::JULIAN).unwrap();
+        assert_eq!((julian.years(), julian.days(), julian.hours()), (1, 364, 18));
+        assert_eq!(decompose_in(time, Calendar::default()).unwrap(),
+                   time.decompose().unwrap());
+
+        let sidereal = decompose_in(Duration::from_secs(86_400), Calendar::SIDEREAL).unwrap();
+        assert_eq!(sidereal.to_string(), "1d 00:03:55.909'500");
+    }
+
+    #[test]
+    fn test_custom_calendar() {
+        let mars = Calendar::new(Duration::from_millis(88_775_244),
+                                 Duration::from_secs(59_355_072))
+            .unwrap();
+        assert_eq!(mars.day(), Duration::from_millis(88_775_244));
+        let time = decompose_in(Duration::from_secs(88_775_244 / 1000 * 3), mars).unwrap();
+        assert_eq!((time.days(), time.hours(), time.minutes()), (2, 24, 39));
+    }
+
+    #[test]
+    fn test_invalid_calendar() {
+        assert_eq!(Calendar::new(Duration::from_secs(2), Duration::from_secs(1)),
+                   Err(FormatError::ValueOutOfRange));
+        assert_eq!(Calendar::new(Duration::from_secs(0), Duration::from_secs(1)),
+                   Err(FormatError::ValueOutOfRange));
+    }
+
+    #[test]
+    fn test_too_many_days() {
+        let tiny = Calendar::new(Duration::from_nanos(1), Duration::from_secs(3600)).unwrap();
+        assert_eq!(decompose_in(Duration::from_secs(3599), tiny), Err(DecomposeError::Overflow));
+    }
+}
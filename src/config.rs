@@ -0,0 +1,111 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use env;
+
+// A `Duration` read from a configuration file, where it may be written as whole seconds
+// (`timeout = 30`), fractional seconds (`timeout = 2.5`) or any string `env::parse` accepts
+// (`timeout = "1m30s"`, `"01:30"`, `"PT90S"`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FlexibleDuration(pub Duration);
+
+impl FlexibleDuration {
+    pub fn get(&self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for FlexibleDuration {
+    fn from(duration: Duration) -> FlexibleDuration {
+        FlexibleDuration(duration)
+    }
+}
+
+impl From<FlexibleDuration> for Duration {
+    fn from(duration: FlexibleDuration) -> Duration {
+        duration.0
+    }
+}
+
+impl<'de> Deserialize<'de> for FlexibleDuration {
+    fn deserialize<D>(deserializer: D) -> Result<FlexibleDuration, D::Error>
+        where D: Deserializer<'de>
+    {
+        deserializer.deserialize_any(FlexibleVisitor)
+    }
+}
+
+struct FlexibleVisitor;
+
+impl<'de> Visitor<'de> for FlexibleVisitor {
+    type Value = FlexibleDuration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number of seconds or a duration string such as \"1m30s\"")
+    }
+
+    fn visit_u64<E>(self, secs: u64) -> Result<FlexibleDuration, E>
+        where E: de::Error
+    {
+        Ok(FlexibleDuration(Duration::from_secs(secs)))
+    }
+
+    fn visit_i64<E>(self, secs: i64) -> Result<FlexibleDuration, E>
+        where E: de::Error
+    {
+        if secs < 0 {
+            return Err(de::Error::invalid_value(de::Unexpected::Signed(secs), &self));
+        }
+        self.visit_u64(secs as u64)
+    }
+
+    fn visit_f64<E>(self, secs: f64) -> Result<FlexibleDuration, E>
+        where E: de::Error
+    {
+        if !(secs >= 0.0 && secs < u64::MAX as f64) {
+            return Err(de::Error::invalid_value(de::Unexpected::Float(secs), &self));
+        }
+        Ok(FlexibleDuration(Duration::from_secs_f64(secs)))
+    }
+
+    fn visit_str<E>(self, text: &str) -> Result<FlexibleDuration, E>
+        where E: de::Error
+    {
+        env::parse(text)
+            .map(FlexibleDuration)
+            .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(text), &self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, Token};
+
+    #[test]
+    fn test_deserialize() {
+        let secs = |secs: u64| FlexibleDuration(Duration::from_secs(secs));
+        assert_de_tokens(&secs(30), &[Token::U64(30)]);
+        assert_de_tokens(&secs(30), &[Token::I64(30)]);
+        assert_de_tokens(&FlexibleDuration(Duration::from_millis(2_500)), &[Token::F64(2.5)]);
+        assert_de_tokens(&secs(90), &[Token::Str("1m30s")]);
+        assert_de_tokens(&secs(90), &[Token::Str("01:30")]);
+        assert_de_tokens(&secs(90), &[Token::Str("PT90S")]);
+
+        assert_de_tokens_error::<FlexibleDuration>(&[Token::I64(-1)],
+                                                   "invalid value: integer `-1`, expected a \
+                                                    number of seconds or a duration string \
+                                                    such as \"1m30s\"");
+        assert_de_tokens_error::<FlexibleDuration>(&[Token::Str("soon")],
+                                                   "invalid value: string \"soon\", expected a \
+                                                    number of seconds or a duration string \
+                                                    such as \"1m30s\"");
+        assert_de_tokens_error::<FlexibleDuration>(&[Token::F64(-0.5)],
+                                                   "invalid value: floating point `-0.5`, \
+                                                    expected a number of seconds or a duration \
+                                                    string such as \"1m30s\"");
+    }
+}
@@ -1,27 +1,118 @@
+use std::convert::Infallible;
+#[cfg(any(feature = "chrono", feature = "prost"))]
+use std::convert::TryFrom;
+use std::array;
+use std::error;
 use std::fmt;
+use std::str;
 use std::time;
 use std::u64;
 
 #[cfg(feature = "float_duration")]
-use float_duration::{duration, FloatDuration};
+use float_duration::FloatDuration;
 
 #[cfg(feature = "chrono")]
 use chrono;
 
-const SECS_PER_YEAR: f64 = SECS_PER_DAY * 365.0;
-const SECS_PER_DAY: f64 = SECS_PER_HOUR * 24.0;
-const SECS_PER_HOUR: f64 = SECS_PER_MINUTE * 60.0;
-const SECS_PER_MINUTE: f64 = 60.0;
-const MILLIS_PER_SEC: f64 = 1000.0;
-const MICROS_PER_SEC: f64 = 1.0e6;
-const NANOS_PER_SEC: f64 = 1.0e9;
+#[cfg(feature = "defmt")]
+use defmt;
+
+#[cfg(feature = "prost")]
+use prost_types;
+
+#[cfg(feature = "wasm")]
+use wasm::Millis;
+
+use fmt::{FormatError, LeadingZeros, SubsecondStyle, ZeroStyle};
+use units::{self, Unit};
+
+#[cfg(feature = "serde")]
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 
 pub trait Decompose {
-    type Error;
+    type Error: Into<DecomposeError>;
     fn decompose(self) -> Result<DecomposedTime, Self::Error>;
+
+    // The undecomposed value in seconds, for callers that want to show something even when
+    // `decompose` fails.
+    fn raw_seconds(&self) -> Option<f64> {
+        None
+    }
+}
+
+// Lets a third-party duration type opt into `Decompose` with one small impl. `as_nanos` is
+// preferred when provided, since it decomposes exactly.
+pub trait SecondsLike {
+    fn as_seconds_f64(&self) -> f64;
+    fn as_nanos(&self) -> Option<i128> {
+        None
+    }
+}
+
+// How float inputs that are effectively zero (-0.0, 1e-12) are decomposed. `Tolerance(t)`
+// turns any magnitude below `t` seconds into the positive zero; `Exact` keeps the sign and the
+// sub-nanosecond residue in `fractional_seconds`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloatZero {
+    Tolerance(f64),
+    Exact,
+}
+
+// How `quantize` snaps a duration that falls between two steps. Directions are by magnitude,
+// so a negative duration rounded `TowardZero` gets shorter, just like a positive one; `Nearest`
+// rounds halfway values away from zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    TowardZero,
+    AwayFromZero,
+    #[default]
+    Nearest,
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecomposeError {
+    Overflow,
+    NotFinite,
+    Negative,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryFromSecsError {
+    Negative,
+    Nan,
+    Overflow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseDisplayError;
+
+// `Display` for a `DecomposedTime` with a non-default leading zero policy. Only the default
+// output is accepted by `from_display_str`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayWith<'a> {
+    time: &'a DecomposedTime,
+    leading_zeros: LeadingZeros,
+    subseconds: SubsecondStyle,
+    zero_style: ZeroStyle<'a>,
+    min_unit: Unit,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DecomposedTimeBuilder {
+    negative: bool,
+    years: u64,
+    days: u64,
+    hours: u64,
+    minutes: u64,
+    seconds: u64,
+    milliseconds: u64,
+    microseconds: u64,
+    nanoseconds: u64,
+}
+
+#[derive(Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct DecomposedTime {
     sign_num: i8,
     years: u64,
@@ -44,7 +135,7 @@ impl DecomposedTime {
                fractional_seconds: f64,
                is_positive: bool)
                -> DecomposedTime {
-        let sign_num = if is_positive { 1 } else { 0 };
+        let sign_num = if is_positive { 1 } else { -1 };
         let (milliseconds, microseconds, nanoseconds) =
             decompose_fractional_seconds(fractional_seconds);
         DecomposedTime {
@@ -60,23 +151,67 @@ impl DecomposedTime {
             fractional_seconds,
         }
     }
+    pub fn builder() -> DecomposedTimeBuilder {
+        DecomposedTimeBuilder::default()
+    }
+    // Parses the output of `Display` back, e.g. "2yr 182d 12:00:00" or "00:30.500'000'100".
+    pub fn from_display_str(s: &str) -> Result<DecomposedTime, ParseDisplayError> {
+        parse_display_str(s).ok_or(ParseDisplayError)
+    }
+    pub fn try_from_secs_f64(secs: f64) -> Result<DecomposedTime, TryFromSecsError> {
+        if secs.is_nan() {
+            Err(TryFromSecsError::Nan)
+        } else if secs < 0.0 {
+            Err(TryFromSecsError::Negative)
+        } else {
+            decomposed_from_float_seconds(secs.abs()).map_err(|_| TryFromSecsError::Overflow)
+        }
+    }
+    pub fn from_secs_f64_with(secs: f64,
+                              zero: FloatZero)
+                              -> Result<DecomposedTime, DecomposeError> {
+        decomposed_from_float_seconds_with(secs, zero)
+    }
+    pub fn try_from_secs_f32(secs: f32) -> Result<DecomposedTime, TryFromSecsError> {
+        DecomposedTime::try_from_secs_f64(secs as f64)
+    }
+    // Exact integer construction from a seconds and nanoseconds pair, the way `std` and chrono
+    // durations are stored. `nanos` may be a second or more and carries into `secs`.
+    pub fn from_secs_nanos(secs: u64, nanos: u32, is_positive: bool) -> DecomposedTime {
+        let nanos = secs as u128 * units::NANOS_PER_SEC as u128 + nanos as u128;
+        decomposed_from_total_nanos(is_positive, nanos).expect("u64 seconds are always in range")
+    }
+    // Exact integer construction for spans far beyond what f64 seconds can hold to the second,
+    // such as geological or astronomical ages.
+    pub fn from_total_seconds(secs: u128,
+                              is_positive: bool)
+                              -> Result<DecomposedTime, DecomposeError> {
+        decomposed_from_total_secs(is_positive, secs, 0).ok_or(DecomposeError::Overflow)
+    }
     pub fn years(&self) -> u64 {
         self.years
     }
-    pub fn total_days(&self) -> Option<u64> {
-        self.years()
-            .checked_mul(365)
-            .and_then(|x| x.checked_add(self.days() as u64))
+    // Totals are u128, which holds them for every representable time. They used to be
+    // `Option<u64>`, `None` on overflow; callers matching on the `Option` need updating.
+    pub fn total_days(&self) -> u128 {
+        self.years() as u128 * 365 + self.days() as u128
     }
     pub fn days(&self) -> u32 {
         self.days
     }
-    pub fn total_hours(&self) -> Option<u64> {
-        self.years()
-            .checked_mul(365)
-            .and_then(|x| x.checked_add(self.days() as u64))
-            .and_then(|x| x.checked_mul(24))
-            .and_then(|x| x.checked_add(self.hours() as u64))
+    pub fn total_hours(&self) -> u128 {
+        self.total_days() * 24 + self.hours() as u128
+    }
+    pub fn total_seconds(&self) -> u128 {
+        self.total_hours() * 3600 + self.minutes() as u128 * 60 + self.seconds() as u128
+    }
+    pub fn total_microseconds(&self) -> Option<u128> {
+        self.total_nanoseconds().map(|nanos| nanos / 1_000)
+    }
+    pub fn total_nanoseconds(&self) -> Option<u128> {
+        self.total_seconds()
+            .checked_mul(units::NANOS_PER_SEC as u128)
+            .and_then(|nanos| nanos.checked_add(self.subsec_nanoseconds() as u128))
     }
     pub fn hours(&self) -> u32 {
         self.hours
@@ -96,34 +231,182 @@ impl DecomposedTime {
     pub fn nanoseconds(&self) -> u32 {
         self.nanoseconds
     }
+    pub fn subsec_nanoseconds(&self) -> u32 {
+        self.milliseconds * 1_000_000 + self.microseconds * 1_000 + self.nanoseconds
+    }
     pub fn fractional_seconds(&self) -> f64 {
         self.fractional_seconds
     }
+    // The part of `fractional_seconds` below the nanosecond field, in nanoseconds. Only times
+    // decomposed from floats carry one; it is otherwise dropped from every field and total.
+    pub fn sub_nano_remainder(&self) -> f64 {
+        self.fractional_seconds * units::NANOS_PER_SEC as f64 - self.subsec_nanoseconds() as f64
+    }
+    pub fn fields(&self) -> array::IntoIter<(Unit, u64), 8> {
+        IntoIterator::into_iter([(Unit::Years, self.years),
+                                 (Unit::Days, self.days as u64),
+                                 (Unit::Hours, self.hours as u64),
+                                 (Unit::Minutes, self.minutes as u64),
+                                 (Unit::Seconds, self.seconds as u64),
+                                 (Unit::Milliseconds, self.milliseconds as u64),
+                                 (Unit::Microseconds, self.microseconds as u64),
+                                 (Unit::Nanoseconds, self.nanoseconds as u64)])
+    }
+    // Field names paired with their values, most significant first, for structured logging.
+    pub fn as_kv(&self) -> [(&'static str, u64); 8] {
+        let mut kv = [("", 0); 8];
+        for (pair, (unit, value)) in kv.iter_mut().zip(self.fields()) {
+            *pair = (unit.name(), value);
+        }
+        kv
+    }
+    // Months aren't a field and read as zero.
+    pub fn get(&self, unit: Unit) -> u64 {
+        match unit {
+            Unit::Years => self.years,
+            Unit::Months => 0,
+            Unit::Days => self.days as u64,
+            Unit::Hours => self.hours as u64,
+            Unit::Minutes => self.minutes as u64,
+            Unit::Seconds => self.seconds as u64,
+            Unit::Milliseconds => self.milliseconds as u64,
+            Unit::Microseconds => self.microseconds as u64,
+            Unit::Nanoseconds => self.nanoseconds as u64,
+        }
+    }
+    // Like the `with_*` builders, but returns `ValueOutOfRange` instead of panicking when `value`
+    // doesn't fit below the next larger unit, and `UnknownField` for months.
+    pub fn try_with(mut self, unit: Unit, value: u64) -> Result<DecomposedTime, FormatError> {
+        let index = Unit::ALL.iter().position(|&u| u == unit).ok_or(FormatError::UnknownField)?;
+        if index > 0 && value >= Unit::ALL[index - 1].nanoseconds() / unit.nanoseconds() {
+            return Err(FormatError::ValueOutOfRange);
+        }
+
+        match unit {
+            Unit::Years => self.set_years(value),
+            Unit::Months => unreachable!("months are not a field"),
+            Unit::Days => self.set_days(value as u32),
+            Unit::Hours => self.set_hours(value as u32),
+            Unit::Minutes => self.set_minutes(value as u32),
+            Unit::Seconds => self.set_seconds(value as u32),
+            Unit::Milliseconds => self.set_milliseconds(value as u32),
+            Unit::Microseconds => self.set_microseconds(value as u32),
+            Unit::Nanoseconds => self.set_nanoseconds(value as u32),
+        }
+        Ok(self)
+    }
+    // The position on a 12-hour dial nearest to this duration, rounded to the half hour, as
+    // (hour in 1..=12, minute of 0 or 30).
+    pub fn nearest_half_hour(&self) -> (u32, u32) {
+        let half_hours = (self.total_seconds() + 15 * 60) / (30 * 60);
+        let hour = (half_hours / 2 % 12) as u32;
+        (if hour == 0 { 12 } else { hour }, if half_hours % 2 == 1 { 30 } else { 0 })
+    }
+    // Whether every field is within its unit's range. `new` takes its fields as given, so a
+    // time built from out-of-range parts fails this.
+    pub fn is_normalized(&self) -> bool {
+        self.days < 365 && self.hours < 24 && self.minutes < 60 && self.seconds < 60 &&
+        self.milliseconds < 1000 && self.microseconds < 1000 && self.nanoseconds < 1000 &&
+        self.fractional_seconds >= 0.0 && self.fractional_seconds < 1.0
+    }
+    // Zeroes every field smaller than `unit`.
+    pub fn truncated(mut self, unit: Unit) -> DecomposedTime {
+        if unit == Unit::Nanoseconds {
+            return self;
+        }
+        for &smaller in Unit::ALL.iter().filter(|&&smaller| smaller < unit) {
+            match smaller {
+                Unit::Years | Unit::Months => unreachable!(),
+                Unit::Days => self.days = 0,
+                Unit::Hours => self.hours = 0,
+                Unit::Minutes => self.minutes = 0,
+                Unit::Seconds => self.seconds = 0,
+                Unit::Milliseconds => self.milliseconds = 0,
+                Unit::Microseconds => self.microseconds = 0,
+                Unit::Nanoseconds => self.nanoseconds = 0,
+            }
+        }
+        self.sync_fractional_seconds();
+        self
+    }
+    // Rounds the sub-nanosecond remainder into the nanosecond field, carrying into the larger
+    // fields, and clears it from `fractional_seconds`.
+    pub fn rounded_to_nanoseconds(mut self) -> DecomposedTime {
+        if self.sub_nano_remainder() >= 0.5 {
+            let rounded = self.total_nanoseconds()
+                .and_then(|nanos| decomposed_from_total_nanos(self.is_positive(), nanos + 1));
+            if let Some(rounded) = rounded {
+                return rounded;
+            }
+        }
+        self.sync_fractional_seconds();
+        self
+    }
+    #[must_use]
     pub fn with_years(mut self, years: u64) -> DecomposedTime {
-        self.years = years;
+        self.set_years(years);
         self
     }
+    #[must_use]
     pub fn with_days(mut self, days: u32) -> DecomposedTime {
-        assert!(days < 365, "days out of bounds");
-        self.days = days;
+        self.set_days(days);
         self
     }
+    #[must_use]
     pub fn with_hours(mut self, hours: u32) -> DecomposedTime {
-        assert!(hours < 24, "hours out of bounds");
-        self.hours = hours;
+        self.set_hours(hours);
         self
     }
+    #[must_use]
     pub fn with_minutes(mut self, mins: u32) -> DecomposedTime {
-        assert!(mins < 60, "minutes out of bounds");
-        self.minutes = mins;
+        self.set_minutes(mins);
         self
     }
+    #[must_use]
     pub fn with_seconds(mut self, secs: u32) -> DecomposedTime {
-        assert!(secs < 60, "seconds out of bounds");
-        self.seconds = secs;
+        self.set_seconds(secs);
         self
     }
+    #[must_use]
     pub fn with_fractional_seconds(mut self, frac: f64) -> DecomposedTime {
+        self.set_fractional_seconds(frac);
+        self
+    }
+    pub fn set_years(&mut self, years: u64) {
+        self.years = years;
+    }
+    pub fn set_days(&mut self, days: u32) {
+        assert!(days < 365, "days out of bounds");
+        self.days = days;
+    }
+    pub fn set_hours(&mut self, hours: u32) {
+        assert!(hours < 24, "hours out of bounds");
+        self.hours = hours;
+    }
+    pub fn set_minutes(&mut self, mins: u32) {
+        assert!(mins < 60, "minutes out of bounds");
+        self.minutes = mins;
+    }
+    pub fn set_seconds(&mut self, secs: u32) {
+        assert!(secs < 60, "seconds out of bounds");
+        self.seconds = secs;
+    }
+    pub fn set_milliseconds(&mut self, millis: u32) {
+        assert!(millis < 1000, "milliseconds out of bounds");
+        self.milliseconds = millis;
+        self.sync_fractional_seconds();
+    }
+    pub fn set_microseconds(&mut self, micros: u32) {
+        assert!(micros < 1000, "microseconds out of bounds");
+        self.microseconds = micros;
+        self.sync_fractional_seconds();
+    }
+    pub fn set_nanoseconds(&mut self, nanos: u32) {
+        assert!(nanos < 1000, "nanoseconds out of bounds");
+        self.nanoseconds = nanos;
+        self.sync_fractional_seconds();
+    }
+    pub fn set_fractional_seconds(&mut self, frac: f64) {
         assert!(frac < 1.0 && frac >= 0.0,
                 "fractional_seconds out of bounds");
 
@@ -132,10 +415,16 @@ impl DecomposedTime {
         self.microseconds = microseconds;
         self.nanoseconds = nanoseconds;
         self.fractional_seconds = frac;
-        self
+    }
+    pub fn set_negative(&mut self, negative: bool) {
+        self.sign_num = if negative { -1 } else { 1 };
+    }
+
+    fn sync_fractional_seconds(&mut self) {
+        self.fractional_seconds = self.subsec_nanoseconds() as f64 / units::NANOS_PER_SEC as f64;
     }
 
-    pub fn zero() -> DecomposedTime {
+    pub const fn zero() -> DecomposedTime {
         DecomposedTime {
             sign_num: 1,
             years: 0,
@@ -150,6 +439,17 @@ impl DecomposedTime {
         }
     }
 
+    #[must_use]
+    pub fn display_with(&self, leading_zeros: LeadingZeros) -> DisplayWith<'_> {
+        DisplayWith {
+            time: self,
+            leading_zeros,
+            subseconds: SubsecondStyle::Trim,
+            zero_style: ZeroStyle::Normal,
+            min_unit: Unit::Nanoseconds,
+        }
+    }
+
     pub fn is_positive(&self) -> bool {
         self.sign_num.is_positive()
     }
@@ -161,20 +461,126 @@ impl DecomposedTime {
     }
 }
 
+impl DecomposedTimeBuilder {
+    pub fn negative(mut self, negative: bool) -> DecomposedTimeBuilder {
+        self.negative = negative;
+        self
+    }
+    pub fn years(mut self, years: u64) -> DecomposedTimeBuilder {
+        self.years = years;
+        self
+    }
+    pub fn days(mut self, days: u64) -> DecomposedTimeBuilder {
+        self.days = days;
+        self
+    }
+    pub fn hours(mut self, hours: u64) -> DecomposedTimeBuilder {
+        self.hours = hours;
+        self
+    }
+    pub fn minutes(mut self, minutes: u64) -> DecomposedTimeBuilder {
+        self.minutes = minutes;
+        self
+    }
+    pub fn seconds(mut self, seconds: u64) -> DecomposedTimeBuilder {
+        self.seconds = seconds;
+        self
+    }
+    pub fn milliseconds(mut self, milliseconds: u64) -> DecomposedTimeBuilder {
+        self.milliseconds = milliseconds;
+        self
+    }
+    pub fn microseconds(mut self, microseconds: u64) -> DecomposedTimeBuilder {
+        self.microseconds = microseconds;
+        self
+    }
+    pub fn nanoseconds(mut self, nanoseconds: u64) -> DecomposedTimeBuilder {
+        self.nanoseconds = nanoseconds;
+        self
+    }
+
+    fn fields(&self) -> [(Unit, u64); 8] {
+        [(Unit::Years, self.years),
+         (Unit::Days, self.days),
+         (Unit::Hours, self.hours),
+         (Unit::Minutes, self.minutes),
+         (Unit::Seconds, self.seconds),
+         (Unit::Milliseconds, self.milliseconds),
+         (Unit::Microseconds, self.microseconds),
+         (Unit::Nanoseconds, self.nanoseconds)]
+    }
+
+    pub fn build(&self) -> Result<DecomposedTime, FormatError> {
+        // Each field is below 2^64 and each factor below 2^55, so the sum can't overflow u128.
+        let nanos = self.fields()
+            .iter()
+            .map(|&(unit, value)| value as u128 * unit.nanoseconds() as u128)
+            .sum();
+
+        decomposed_from_total_nanos(!self.negative, nanos).ok_or(FormatError::ValueOutOfRange)
+    }
+}
+
+impl fmt::Display for TryFromSecsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            TryFromSecsError::Negative => "cannot convert float seconds: value is negative",
+            TryFromSecsError::Nan => "cannot convert float seconds: value is NaN",
+            TryFromSecsError::Overflow => "cannot convert float seconds: value is too large",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl error::Error for TryFromSecsError {}
+
+impl fmt::Display for DecomposeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            DecomposeError::Overflow => "cannot decompose duration: value is too large",
+            DecomposeError::NotFinite => "cannot decompose duration: value is not finite",
+            DecomposeError::Negative => "cannot decompose duration: value is negative",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl error::Error for DecomposeError {}
+
+impl Default for FloatZero {
+    // Anything that would decompose to zero nanoseconds counts as zero.
+    fn default() -> FloatZero {
+        FloatZero::Tolerance(1.0e-9)
+    }
+}
+
+impl From<Infallible> for DecomposeError {
+    fn from(never: Infallible) -> DecomposeError {
+        match never {}
+    }
+}
+
+impl From<TryFromSecsError> for DecomposeError {
+    fn from(err: TryFromSecsError) -> DecomposeError {
+        match err {
+            TryFromSecsError::Negative => DecomposeError::Negative,
+            TryFromSecsError::Nan => DecomposeError::NotFinite,
+            TryFromSecsError::Overflow => DecomposeError::Overflow,
+        }
+    }
+}
+
+impl fmt::Display for ParseDisplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("string is not a displayed duration")
+    }
+}
+
+impl error::Error for ParseDisplayError {}
+
 impl Default for DecomposedTime {
     fn default() -> DecomposedTime {
-        DecomposedTime {
-            sign_num: 1,
-            years: 0,
-            days: 0,
-            hours: 0,
-            minutes: 0,
-            seconds: 0,
-            milliseconds: 0,
-            microseconds: 0,
-            nanoseconds: 0,
-            fractional_seconds: 0.0,
-        }
+        DecomposedTime::zero()
     }
 }
 
@@ -182,110 +588,532 @@ impl Default for DecomposedTime {
 impl From<DecomposedTime> for FloatDuration {
     fn from(time: DecomposedTime) -> FloatDuration {
         FloatDuration::seconds(time.signum() as f64 *
-                               (duration::SECS_PER_YEAR * time.years() as f64 +
-                                duration::SECS_PER_DAY * time.days() as f64 +
-                                duration::SECS_PER_HOUR * time.hours() as f64 +
-                                duration::SECS_PER_MINUTE * time.minutes() as f64 +
+                               (units::SECS_PER_YEAR as f64 * time.years() as f64 +
+                                units::SECS_PER_DAY as f64 * time.days() as f64 +
+                                units::SECS_PER_HOUR as f64 * time.hours() as f64 +
+                                units::SECS_PER_MINUTE as f64 * time.minutes() as f64 +
                                 time.seconds() as f64 +
                                 time.fractional_seconds))
     }
 }
 
+impl<T> Decompose for T
+    where T: SecondsLike
+{
+    type Error = DecomposeError;
+    fn decompose(self) -> Result<DecomposedTime, DecomposeError> {
+        match self.as_nanos() {
+            Some(nanos) => {
+                decomposed_from_total_nanos(nanos >= 0, nanos.unsigned_abs())
+                    .ok_or(DecomposeError::Overflow)
+            }
+            None => decomposed_from_float_seconds(self.as_seconds_f64()),
+        }
+    }
+    fn raw_seconds(&self) -> Option<f64> {
+        Some(self.as_seconds_f64())
+    }
+}
 #[cfg(feature = "float_duration")]
 impl Decompose for FloatDuration {
-    //TODO: Handle: NAN, INF
-    type Error = ();
-    fn decompose(self) -> Result<DecomposedTime, ()> {
-        Ok(decomposed_from_float_seconds(self.as_seconds()))
+    type Error = DecomposeError;
+    fn decompose(self) -> Result<DecomposedTime, DecomposeError> {
+        decomposed_from_float_seconds(self.as_seconds())
+    }
+    fn raw_seconds(&self) -> Option<f64> {
+        Some(self.as_seconds())
     }
 }
 #[cfg(feature = "chrono")]
-impl Decompose for chrono::Duration {
-    type Error = ();
-    fn decompose(self) -> Result<DecomposedTime, ()> {
-        if let Some(nanos) = self.num_nanoseconds() {
-            Ok(decomposed_from_float_seconds((nanos as f64) * NANOS_PER_SEC))
-        } else if let Some(micros) = self.num_microseconds() {
-            Ok(decomposed_from_float_seconds((micros as f64) * MICROS_PER_SEC))
-        } else {
-            Ok(decomposed_from_float_seconds((self.num_milliseconds() as f64) * MILLIS_PER_SEC))
+impl Decompose for chrono::TimeDelta {
+    type Error = Infallible;
+    fn decompose(self) -> Result<DecomposedTime, Infallible> {
+        let (secs, nanos) = (self.num_seconds(), self.subsec_nanos());
+        Ok(DecomposedTime::from_secs_nanos(secs.unsigned_abs(),
+                                           nanos.unsigned_abs(),
+                                           secs >= 0 && nanos >= 0))
+    }
+}
+#[cfg(feature = "chrono")]
+impl TryFrom<DecomposedTime> for chrono::TimeDelta {
+    type Error = FormatError;
+    fn try_from(time: DecomposedTime) -> Result<chrono::TimeDelta, FormatError> {
+        let secs = i64::try_from(time.total_seconds()).map_err(|_| FormatError::ValueOutOfRange)?;
+        let delta = chrono::TimeDelta::new(secs, time.subsec_nanoseconds())
+            .ok_or(FormatError::ValueOutOfRange)?;
+        Ok(if time.is_negative() { -delta } else { delta })
+    }
+}
+// Protobuf durations may arrive with seconds and nanos of mixed sign; they are normalized first.
+#[cfg(feature = "prost")]
+impl Decompose for prost_types::Duration {
+    type Error = Infallible;
+    fn decompose(mut self) -> Result<DecomposedTime, Infallible> {
+        self.normalize();
+        Ok(DecomposedTime::from_secs_nanos(self.seconds.unsigned_abs(),
+                                           self.nanos.unsigned_abs(),
+                                           self.seconds >= 0 && self.nanos >= 0))
+    }
+}
+#[cfg(feature = "prost")]
+impl From<prost_types::Duration> for DecomposedTime {
+    fn from(duration: prost_types::Duration) -> DecomposedTime {
+        match duration.decompose() {
+            Ok(time) => time,
+            Err(never) => match never {},
         }
     }
 }
+#[cfg(feature = "prost")]
+impl TryFrom<DecomposedTime> for prost_types::Duration {
+    type Error = FormatError;
+    fn try_from(time: DecomposedTime) -> Result<prost_types::Duration, FormatError> {
+        let seconds = i64::try_from(time.total_seconds())
+            .map_err(|_| FormatError::ValueOutOfRange)?;
+        let nanos = time.subsec_nanoseconds() as i32;
+        Ok(if time.is_negative() {
+               prost_types::Duration {
+                   seconds: -seconds,
+                   nanos: -nanos,
+               }
+           } else {
+               prost_types::Duration { seconds, nanos }
+           })
+    }
+}
 impl Decompose for time::Duration {
-    type Error = ();
-    fn decompose(self) -> Result<DecomposedTime, ()> {
-        Ok(decomposed_from_float_seconds(self.as_secs() as f64 +
-                                         (self.subsec_nanos() as f64) * NANOS_PER_SEC))
+    type Error = Infallible;
+    fn decompose(self) -> Result<DecomposedTime, Infallible> {
+        Ok(DecomposedTime::from_secs_nanos(self.as_secs(), self.subsec_nanos(), true))
     }
 }
+// JavaScript represents durations as f64 milliseconds (`Date.now() - start`).
+#[cfg(feature = "wasm")]
+impl Decompose for Millis {
+    type Error = DecomposeError;
+    fn decompose(self) -> Result<DecomposedTime, DecomposeError> {
+        decomposed_from_float_seconds(self.0 / units::MILLIS_PER_SEC as f64)
+    }
+    fn raw_seconds(&self) -> Option<f64> {
+        Some(self.0 / units::MILLIS_PER_SEC as f64)
+    }
+}
+impl str::FromStr for DecomposedTime {
+    type Err = ParseDisplayError;
+    fn from_str(s: &str) -> Result<DecomposedTime, ParseDisplayError> {
+        DecomposedTime::from_display_str(s)
+    }
+}
+
 impl Decompose for DecomposedTime {
-    type Error = ();
-    fn decompose(self) -> Result<DecomposedTime, ()> {
+    type Error = Infallible;
+    fn decompose(self) -> Result<DecomposedTime, Infallible> {
         Ok(self)
     }
 }
 
+// Shows the normalized value next to the raw fields, so a decomposition that looks off can be
+// checked against its nanosecond total without a debugger.
+impl fmt::Debug for DecomposedTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total_nanos = self.total_seconds() * units::NANOS_PER_SEC as u128 +
+                          self.subsec_nanoseconds() as u128;
+
+        let mut debug = f.debug_struct("DecomposedTime");
+        debug.field("value", &format_args!("{}", self))
+            .field("sign", &self.sign_num)
+            .field("total_nanoseconds", &total_nanos);
+        for (unit, value) in self.fields() {
+            debug.field(unit.name(), &value);
+        }
+        debug.finish()
+    }
+}
+
+// Negative times are written with a leading '-', "-01:30.500"; older releases printed them the
+// same as positive ones.
 impl fmt::Display for DecomposedTime {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.years() > 0 {
-            write!(f, "{}yr ", self.years())?;
+        self.display_with(LeadingZeros::All).fmt(f)
+    }
+}
+
+impl<'a> DisplayWith<'a> {
+    #[must_use]
+    pub fn with_subseconds(mut self, subseconds: SubsecondStyle) -> DisplayWith<'a> {
+        self.subseconds = subseconds;
+        self
+    }
+    #[must_use]
+    pub fn with_zero_style(mut self, zero_style: ZeroStyle<'a>) -> DisplayWith<'a> {
+        self.zero_style = zero_style;
+        self
+    }
+    // Leaves out every field smaller than `unit`, so multi-year spans can read "2yr 182d".
+    // From hours up the clock is replaced by unit suffixes ("3d 5h"); `Minutes` gives "H:MM".
+    #[must_use]
+    pub fn with_min_unit(mut self, unit: Unit) -> DisplayWith<'a> {
+        self.min_unit = unit;
+        self
+    }
+
+    fn write_large_units(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let time = self.time;
+        let parts = [(Unit::Years, time.years(), "yr"),
+                     (Unit::Days, time.days() as u64, "d"),
+                     (Unit::Hours, time.hours() as u64, "h")];
+        let mut first = true;
+        for &(unit, value, suffix) in parts.iter().filter(|&&(unit, _, _)| unit >= self.min_unit) {
+            if value > 0 || (unit == self.min_unit && first) {
+                write!(f, "{}{}{}", if first { "" } else { " " }, value, suffix)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for DisplayWith<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let time = self.time;
+        if let Some(text) = self.zero_style.replacement(time) {
+            return f.write_str(text);
+        }
+        if time.is_negative() {
+            write!(f, "-")?;
+        }
+        if self.min_unit >= Unit::Hours {
+            return self.write_large_units(f);
+        }
+        if time.years() > 0 {
+            write!(f, "{}yr ", time.years())?;
         }
-        if self.days() > 0 {
-            write!(f, "{}d ", self.days())?;
+        if time.days() > 0 {
+            write!(f, "{}d ", time.days())?;
         }
-        if self.hours() > 0 || self.days() > 0 || self.years() > 0 {
-            write!(f, "{:02}:", self.hours())?;
+        if self.min_unit == Unit::Minutes {
+            return write!(f,
+                          "{:02$}:{:03$}",
+                          time.hours(),
+                          time.minutes(),
+                          self.leading_zeros.width(true),
+                          self.leading_zeros.width(false));
         }
-        write!(f, "{:02}:{:02}", self.minutes(), self.seconds())?;
+        let show_hours = time.hours() > 0 || time.days() > 0 || time.years() > 0;
+        if show_hours {
+            write!(f, "{:01$}:", time.hours(), self.leading_zeros.width(true))?;
+        }
+        write!(f,
+               "{:02$}:{:03$}",
+               time.minutes(),
+               time.seconds(),
+               self.leading_zeros.width(!show_hours),
+               self.leading_zeros.width(false))?;
 
-        if self.nanoseconds() > 0 {
-            write!(f,
-                   ".{:03}'{:03}'{:03}",
-                   self.milliseconds(),
-                   self.microseconds(),
-                   self.nanoseconds())?;
-        } else if self.microseconds() > 0 {
-            write!(f, ".{:03}'{:03}", self.milliseconds(), self.microseconds())?;
-        } else if self.milliseconds() > 0 {
-            write!(f, ".{:03}", self.milliseconds())?;
+        let groups = [time.milliseconds(), time.microseconds(), time.nanoseconds()];
+        let shown = match self.subseconds {
+            SubsecondStyle::Full => 3,
+            SubsecondStyle::Trim => {
+                groups.iter().rposition(|&group| group > 0).map_or(0, |last| last + 1)
+            }
+            SubsecondStyle::Groups(count) => count.min(3),
+        };
+        let shown = match self.min_unit {
+            Unit::Seconds => 0,
+            Unit::Milliseconds => shown.min(1),
+            Unit::Microseconds => shown.min(2),
+            _ => shown,
+        };
+        for (i, group) in groups[..shown].iter().enumerate() {
+            write!(f, "{}{:03}", if i == 0 { '.' } else { '\'' }, group)?;
         }
         Ok(())
     }
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for DecomposedTime {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f,
+                      "{=str}{=u64}yr {=u32}d {=u32:02}:{=u32:02}:{=u32:02}.{=u32:09}",
+                      if self.is_negative() { "-" } else { "" },
+                      self.years(),
+                      self.days(),
+                      self.hours(),
+                      self.minutes(),
+                      self.seconds(),
+                      self.subsec_nanoseconds())
+    }
+}
+
+// Logs only the signed whole seconds (saturated to u64) and subsecond nanoseconds, keeping the
+// on-wire encoding small.
+#[cfg(feature = "defmt")]
+#[derive(Clone, Copy, Debug)]
+pub struct Compact<'a>(pub &'a DecomposedTime);
+
+#[cfg(feature = "defmt")]
+impl<'a> defmt::Format for Compact<'a> {
+    fn format(&self, f: defmt::Formatter) {
+        let time = self.0;
+        let secs = time.total_seconds();
+        let secs = if secs > u64::MAX as u128 { u64::MAX } else { secs as u64 };
+
+        defmt::write!(f,
+                      "{=str}{=u64}.{=u32:09}s",
+                      if time.is_negative() { "-" } else { "" },
+                      secs,
+                      time.subsec_nanoseconds())
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedDecomposedTime {
+    pub fn years(&self) -> u64 {
+        self.years.to_native()
+    }
+    pub fn days(&self) -> u32 {
+        self.days.to_native()
+    }
+    pub fn hours(&self) -> u32 {
+        self.hours.to_native()
+    }
+    pub fn minutes(&self) -> u32 {
+        self.minutes.to_native()
+    }
+    pub fn seconds(&self) -> u32 {
+        self.seconds.to_native()
+    }
+    pub fn subsec_nanoseconds(&self) -> u32 {
+        self.milliseconds.to_native() * 1_000_000 + self.microseconds.to_native() * 1_000 +
+        self.nanoseconds.to_native()
+    }
+    pub fn fractional_seconds(&self) -> f64 {
+        self.fractional_seconds.to_native()
+    }
+    pub fn signum(&self) -> i8 {
+        self.sign_num
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for DecomposedTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            let secs = self.total_seconds();
+            if secs > u64::MAX as u128 {
+                return Err(ser::Error::custom("duration too long for binary encoding"));
+            }
+            (secs as u64, self.subsec_nanoseconds(), self.signum()).serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DecomposedTime {
+    fn deserialize<D>(deserializer: D) -> Result<DecomposedTime, D::Error>
+        where D: Deserializer<'de>
+    {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            parse_display_str(&s)
+                .ok_or_else(|| de::Error::custom(format_args!("invalid duration `{}`", s)))
+        } else {
+            let (secs, nanos, sign) = <(u64, u32, i8)>::deserialize(deserializer)?;
+            if nanos >= units::NANOS_PER_SEC as u32 {
+                return Err(de::Error::custom("subsecond nanoseconds out of range"));
+            }
+            match sign {
+                1 => Ok(DecomposedTime::from_secs_nanos(secs, nanos, true)),
+                -1 => Ok(DecomposedTime::from_secs_nanos(secs, nanos, false)),
+                _ => Err(de::Error::custom("sign must be 1 or -1")),
+            }
+        }
+    }
+}
+
+// Snaps `time` to a multiple of `step` (5s, 1min, 15min) for reports and calendars that need
+// consistent buckets. A zero `step` is `FormatError::ValueOutOfRange`.
+pub fn quantize<D>(time: D, step: time::Duration, rounding: Rounding)
+                   -> Result<DecomposedTime, FormatError>
+    where D: Decompose
+{
+    let step = step.as_nanos();
+    if step == 0 {
+        return Err(FormatError::ValueOutOfRange);
+    }
+    let time = time.decompose().map_err(|e| e.into())?;
+    let nanos = time.total_nanoseconds().ok_or(DecomposeError::Overflow)?;
+
+    let steps = match rounding {
+        Rounding::TowardZero => nanos / step,
+        Rounding::AwayFromZero => nanos / step + (nanos % step != 0) as u128,
+        Rounding::Nearest => nanos / step + (nanos % step >= step - step / 2) as u128,
+    };
+    steps.checked_mul(step)
+        .and_then(|nanos| decomposed_from_total_nanos(time.is_positive(), nanos))
+        .ok_or(FormatError::DecomposeError(DecomposeError::Overflow))
+}
+
+// Accepts exactly the grammar produced by `Display`:
+// `[-][<years>yr ][<days>d ][HH:]MM:SS[.mmm['uuu['nnn]]]`.
+fn parse_display_str(s: &str) -> Option<DecomposedTime> {
+    let (is_positive, mut rest) = match s.strip_prefix('-') {
+        Some(rest) => (false, rest),
+        None => (true, s),
+    };
+
+    let mut years = 0;
+    if let Some(idx) = rest.find("yr ") {
+        years = parse_digits(&rest[..idx])?;
+        rest = &rest[idx + 3..];
+    }
+    let mut days = 0;
+    if let Some(idx) = rest.find("d ") {
+        days = parse_digits(&rest[..idx])?;
+        rest = &rest[idx + 2..];
+    }
+
+    let (clock, subsec) = match rest.find('.') {
+        Some(idx) => (&rest[..idx], Some(&rest[idx + 1..])),
+        None => (rest, None),
+    };
+    let clock = clock.split(':').collect::<Vec<_>>();
+    let (hours, minutes, seconds) = match clock[..] {
+        [hours, minutes, seconds] => (parse_two_digits(hours)?, minutes, seconds),
+        [minutes, seconds] if years == 0 && days == 0 => (0, minutes, seconds),
+        _ => return None,
+    };
+    let (minutes, seconds) = (parse_two_digits(minutes)?, parse_two_digits(seconds)?);
+
+    let mut subsec_nanos = 0;
+    if let Some(subsec) = subsec {
+        let groups = subsec.split('\'').collect::<Vec<_>>();
+        if groups.len() > 3 {
+            return None;
+        }
+        let mut scale = 1_000_000;
+        for group in groups {
+            if group.len() != 3 {
+                return None;
+            }
+            subsec_nanos += parse_digits::<u32>(group)? * scale;
+            scale /= 1_000;
+        }
+    }
+
+    if days >= 365 || hours >= 24 || minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    Some(decomposed_from_parts(is_positive, years, days, hours, minutes, seconds, subsec_nanos))
+}
+
+fn parse_digits<T>(s: &str) -> Option<T>
+    where T: str::FromStr
+{
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn parse_two_digits(s: &str) -> Option<u32> {
+    if s.len() != 2 {
+        return None;
+    }
+    parse_digits(s)
+}
+
+fn decomposed_from_total_nanos(is_positive: bool, nanos: u128) -> Option<DecomposedTime> {
+    let nanos_per_sec = units::NANOS_PER_SEC as u128;
+    let (secs, subsec_nanos) = (nanos / nanos_per_sec, nanos % nanos_per_sec);
+    decomposed_from_total_secs(is_positive, secs, subsec_nanos as u32)
+}
+
+fn decomposed_from_total_secs(is_positive: bool,
+                              secs: u128,
+                              subsec_nanos: u32)
+                              -> Option<DecomposedTime> {
+    let (years, days, hours, minutes, seconds) = units::split_secs(secs)?;
+    Some(decomposed_from_parts(is_positive, years, days, hours, minutes, seconds, subsec_nanos))
+}
+
+fn decomposed_from_parts(is_positive: bool,
+                         years: u64,
+                         days: u32,
+                         hours: u32,
+                         minutes: u32,
+                         seconds: u32,
+                         subsec_nanos: u32)
+                         -> DecomposedTime {
+    let (milliseconds, microseconds, nanoseconds) = units::split_subsec_nanos(subsec_nanos);
+    DecomposedTime {
+        sign_num: if is_positive { 1 } else { -1 },
+        years,
+        days,
+        hours,
+        minutes,
+        seconds,
+        milliseconds,
+        microseconds,
+        nanoseconds,
+        fractional_seconds: subsec_nanos as f64 / units::NANOS_PER_SEC as f64,
+    }
+}
 
 fn decompose_fractional_seconds(fractional_seconds: f64) -> (u32, u32, u32) {
     let mut rem_frac = fractional_seconds;
 
-    let milliseconds = (rem_frac * MILLIS_PER_SEC).trunc();
-    rem_frac -= milliseconds / MILLIS_PER_SEC;
-    let microseconds = (rem_frac * MICROS_PER_SEC).trunc();
-    rem_frac -= microseconds / MICROS_PER_SEC;
-    let nanoseconds = (rem_frac * NANOS_PER_SEC).trunc();
+    let milliseconds = (rem_frac * units::MILLIS_PER_SEC as f64).trunc();
+    rem_frac -= milliseconds / units::MILLIS_PER_SEC as f64;
+    let microseconds = (rem_frac * units::MICROS_PER_SEC as f64).trunc();
+    rem_frac -= microseconds / units::MICROS_PER_SEC as f64;
+    let nanoseconds = (rem_frac * units::NANOS_PER_SEC as f64).trunc();
 
     (milliseconds as u32, microseconds as u32, nanoseconds as u32)
 }
 
-fn decomposed_from_float_seconds(secs: f64) -> DecomposedTime {
+fn decomposed_from_float_seconds(secs: f64) -> Result<DecomposedTime, DecomposeError> {
+    decomposed_from_float_seconds_with(secs, FloatZero::default())
+}
+
+fn decomposed_from_float_seconds_with(secs: f64,
+                                      zero: FloatZero)
+                                      -> Result<DecomposedTime, DecomposeError> {
+    if !secs.is_finite() {
+        return Err(DecomposeError::NotFinite);
+    }
+    if let FloatZero::Tolerance(tolerance) = zero {
+        if secs.abs() < tolerance {
+            return Ok(DecomposedTime::zero());
+        }
+    }
+
     let mut rem_seconds = secs.trunc().abs();
     let fractional_seconds = secs.fract().abs();
     let sign_num = secs.signum();
 
-    let years = (rem_seconds / SECS_PER_YEAR).trunc();
-    rem_seconds -= years * SECS_PER_YEAR;
-    let days = (rem_seconds / SECS_PER_DAY).trunc();
-    rem_seconds -= days * SECS_PER_DAY;
-    let hours = (rem_seconds / SECS_PER_HOUR).trunc();
-    rem_seconds -= hours * SECS_PER_HOUR;
-    let minutes = (rem_seconds / SECS_PER_MINUTE).trunc();
-    rem_seconds -= minutes * SECS_PER_MINUTE;
+    let years = (rem_seconds / units::SECS_PER_YEAR as f64).trunc();
+    if years >= u64::MAX as f64 {
+        return Err(DecomposeError::Overflow);
+    }
+    rem_seconds -= years * units::SECS_PER_YEAR as f64;
+    let days = (rem_seconds / units::SECS_PER_DAY as f64).trunc();
+    rem_seconds -= days * units::SECS_PER_DAY as f64;
+    let hours = (rem_seconds / units::SECS_PER_HOUR as f64).trunc();
+    rem_seconds -= hours * units::SECS_PER_HOUR as f64;
+    let minutes = (rem_seconds / units::SECS_PER_MINUTE as f64).trunc();
+    rem_seconds -= minutes * units::SECS_PER_MINUTE as f64;
     let seconds = rem_seconds.trunc();
 
     let (milliseconds, microseconds, nanoseconds) =
         decompose_fractional_seconds(fractional_seconds);
 
-    DecomposedTime {
+    Ok(DecomposedTime {
         years: years as u64,
         days: days as u32,
         hours: hours as u32,
@@ -296,32 +1124,29 @@ fn decomposed_from_float_seconds(secs: f64) -> DecomposedTime {
         nanoseconds: nanoseconds,
         fractional_seconds: fractional_seconds,
         sign_num: sign_num as i8,
-    }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "float_duration")]
     use float_duration::FloatDuration;
 
     #[test]
     fn test_with_builders() {
         let time = DecomposedTime::default().with_years(5).with_days(10);
         assert_eq!(time,
-                   (FloatDuration::years(5.0) + FloatDuration::days(10.0))
-                       .decompose()
-                       .unwrap());
+                   time::Duration::from_secs(5 * 31_536_000 + 10 * 86_400).decompose().unwrap());
 
         assert_eq!(DecomposedTime::zero(),
-                   FloatDuration::zero().decompose().unwrap());
+                   time::Duration::from_secs(0).decompose().unwrap());
         assert_eq!(DecomposedTime::default().with_days(10).with_minutes(30),
-                   (FloatDuration::days(10.0) + FloatDuration::minutes(30.0))
-                       .decompose()
-                       .unwrap());
+                   time::Duration::from_secs(10 * 86_400 + 30 * 60).decompose().unwrap());
         assert_eq!(DecomposedTime::default()
                        .with_seconds(30)
                        .with_fractional_seconds(0.5),
-                   (FloatDuration::seconds(30.5)).decompose().unwrap());
+                   time::Duration::from_millis(30_500).decompose().unwrap());
 
         assert_eq!(DecomposedTime::default()
                        .with_fractional_seconds(0.2)
@@ -337,42 +1162,533 @@ mod tests {
                    50);
     }
 
+    #[test]
+    fn test_totals() {
+        let time = DecomposedTime::default()
+            .with_years(2)
+            .with_days(3)
+            .with_hours(4);
+        assert_eq!(time.total_days(), 733);
+        assert_eq!(time.total_hours(), 733 * 24 + 4);
+
+        let time = DecomposedTime::default().with_years(u64::MAX).with_days(364);
+        assert_eq!(time.total_days(), u64::MAX as u128 * 365 + 364);
+        assert_eq!(time.total_hours(), (u64::MAX as u128 * 365 + 364) * 24);
+    }
+
+    #[test]
+    fn test_exact_totals() {
+        let time = DecomposedTime::builder()
+            .days(1)
+            .seconds(2)
+            .microseconds(3)
+            .nanoseconds(4)
+            .build()
+            .unwrap();
+        assert_eq!(time.total_nanoseconds(), Some(86_402_000_003_004));
+        assert_eq!(time.total_microseconds(), Some(86_402_000_003));
+
+        let max = DecomposedTime::zero().with_years(u64::MAX);
+        assert_eq!(max.total_nanoseconds(),
+                   Some(u64::MAX as u128 * Unit::Years.nanoseconds() as u128));
+    }
+
+    #[cfg(feature = "float_duration")]
+    #[test]
+    fn test_out_of_range() {
+        assert_eq!(FloatDuration::seconds(f64::INFINITY).decompose(),
+                   Err(DecomposeError::NotFinite));
+        assert_eq!(FloatDuration::seconds(f64::NAN).decompose(),
+                   Err(DecomposeError::NotFinite));
+        assert_eq!(FloatDuration::years(1.0e20).decompose(),
+                   Err(DecomposeError::Overflow));
+        assert!(FloatDuration::years(1.0e18).decompose().is_ok());
+        assert_eq!(FloatDuration::seconds(-0.0).decompose().unwrap(), DecomposedTime::zero());
+    }
+
+    #[test]
+    fn test_seconds_like() {
+        struct Ticks(i64);
+        impl SecondsLike for Ticks {
+            fn as_seconds_f64(&self) -> f64 {
+                self.0 as f64 / 1.0e7
+            }
+            fn as_nanos(&self) -> Option<i128> {
+                Some(self.0 as i128 * 100)
+            }
+        }
+        struct Beats(f64);
+        impl SecondsLike for Beats {
+            fn as_seconds_f64(&self) -> f64 {
+                self.0 * 0.5
+            }
+        }
+
+        let time = Ticks(-36_000_000_001).decompose().unwrap();
+        assert!(time.is_negative());
+        assert_eq!((time.hours(), time.seconds(), time.subsec_nanoseconds()), (1, 0, 100));
+        assert_eq!(Beats(3.0).decompose().unwrap().seconds(), 1);
+        assert_eq!(Beats(f64::NAN).decompose(), Err(DecomposeError::NotFinite));
+    }
+
+    #[test]
+    fn test_float_zero() {
+        for &secs in &[-0.0, 1.0e-12, -1.0e-12] {
+            let time = DecomposedTime::from_secs_f64_with(secs, FloatZero::default()).unwrap();
+            assert_eq!(time, DecomposedTime::zero());
+            assert_eq!(time.to_string(), "00:00");
+        }
+
+        let lenient = FloatZero::Tolerance(0.01);
+        assert_eq!(DecomposedTime::from_secs_f64_with(-0.005, lenient).unwrap(),
+                   DecomposedTime::zero());
+        assert_eq!(DecomposedTime::from_secs_f64_with(0.02, lenient).unwrap().milliseconds(),
+                   20);
+
+        let exact = DecomposedTime::from_secs_f64_with(-1.0e-12, FloatZero::Exact).unwrap();
+        assert!(exact.is_negative());
+        assert_eq!(exact.fractional_seconds(), 1.0e-12);
+    }
+
+    #[test]
+    fn test_sub_nano_remainder() {
+        let time = DecomposedTime::try_from_secs_f64(1.000_000_000_75).unwrap();
+        assert_eq!(time.subsec_nanoseconds(), 0);
+        assert!((time.sub_nano_remainder() - 0.75).abs() < 1e-3);
+
+        let rounded = time.rounded_to_nanoseconds();
+        assert_eq!(rounded.subsec_nanoseconds(), 1);
+        assert_eq!(rounded.sub_nano_remainder(), 0.0);
+
+        let carried = DecomposedTime::from_secs_f64_with(-59.999_999_999_9, FloatZero::default())
+            .unwrap();
+        assert_eq!(carried.rounded_to_nanoseconds().to_string(), "-01:00");
+        assert_eq!(DecomposedTime::zero().sub_nano_remainder(), 0.0);
+    }
+
+    #[test]
+    fn test_from_total_seconds() {
+        let age = 4_540_000_000 * 365 * 86_400;
+        let time = DecomposedTime::from_total_seconds(age + 90, true).unwrap();
+        assert_eq!(time.years(), 4_540_000_000);
+        assert_eq!(time.minutes(), 1);
+        assert_eq!(time.seconds(), 30);
+        assert_eq!(time.total_seconds(), age + 90);
+
+        assert_eq!(DecomposedTime::from_total_seconds(u128::MAX, false),
+                   Err(DecomposeError::Overflow));
+    }
+
+    #[test]
+    fn test_new_sign() {
+        let negative = DecomposedTime::new(0, 0, 0, 1, 30, 0.0, false);
+        assert!(negative.is_negative());
+        assert_eq!(negative.signum(), -1);
+        assert_eq!(negative.to_string(), "-01:30");
+
+        let positive = DecomposedTime::new(0, 0, 0, 1, 30, 0.0, true);
+        assert_eq!((positive.is_positive(), positive.signum()), (true, 1));
+    }
+
+    #[test]
+    fn test_from_secs_nanos() {
+        let time = DecomposedTime::from_secs_nanos(0, 1_000_000_001, true);
+        assert_eq!((time.seconds(), time.milliseconds(), time.nanoseconds()), (1, 0, 1));
+        assert_eq!(time.total_nanoseconds(), Some(1_000_000_001));
+        assert_eq!(time, time::Duration::from_nanos(1_000_000_001).decompose().unwrap());
+
+        let time = DecomposedTime::from_secs_nanos(3_661, 250_000_000, false);
+        assert_eq!(time.to_string(), "-01:01:01.250");
+        assert_eq!(DecomposedTime::from_secs_nanos(u64::MAX, 999_999_999, true).total_seconds(),
+                   u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_std_duration() {
+        let time = time::Duration::new(90, 250_000_000).decompose().unwrap();
+        assert_eq!(time.minutes(), 1);
+        assert_eq!(time.seconds(), 30);
+        assert_eq!(time.milliseconds(), 250);
+    }
+
+    // The subsecond nanoseconds were once multiplied by `NANOS_PER_SEC` instead of divided,
+    // turning 999 ms into billions of years.
+    #[test]
+    fn test_std_duration_subsec() {
+        let time = time::Duration::new(0, 999_999_999).decompose().unwrap();
+        assert_eq!((time.years(), time.days(), time.seconds()), (0, 0, 0));
+        assert_eq!((time.milliseconds(), time.microseconds(), time.nanoseconds()),
+                   (999, 999, 999));
+        assert_eq!(time::Duration::from_nanos(1).decompose().unwrap().total_nanoseconds(),
+                   Some(1));
+    }
+
+    #[test]
+    fn test_display_sign() {
+        let negative = DecomposedTime::from_secs_nanos(90, 500_000_000, false);
+        assert_eq!(negative.to_string(), "-01:30.500");
+        assert_eq!(DecomposedTime::from_secs_nanos(90, 500_000_000, true).to_string(),
+                   "01:30.500");
+        assert_eq!(DecomposedTime::from_secs_nanos(86_400, 0, false).to_string(),
+                   "-1d 00:00:00");
+    }
+
+    #[test]
+    fn test_display_leading_zeros() {
+        let time = DecomposedTime::zero().with_hours(2).with_minutes(5);
+        assert_eq!(time.display_with(LeadingZeros::ExceptLargest).to_string(), "2:05:00");
+        assert_eq!(time.display_with(LeadingZeros::None).to_string(), "2:5:0");
+
+        let time = DecomposedTime::zero().with_minutes(2).with_seconds(5);
+        assert_eq!(time.display_with(LeadingZeros::ExceptLargest).to_string(), "2:05");
+        assert_eq!(time.display_with(LeadingZeros::All).to_string(), time.to_string());
+    }
+
+    #[test]
+    fn test_display_subseconds() {
+        let time = DecomposedTime::zero().with_seconds(5).with_fractional_seconds(0.000_05);
+        let display = |style| {
+            time.display_with(LeadingZeros::All).with_subseconds(style).to_string()
+        };
+        assert_eq!(display(SubsecondStyle::Trim), "00:05.000'050");
+        assert_eq!(display(SubsecondStyle::Full), "00:05.000'050'000");
+        assert_eq!(display(SubsecondStyle::Groups(1)), "00:05.000");
+        assert_eq!(display(SubsecondStyle::Groups(0)), "00:05");
+        assert_eq!(DecomposedTime::zero()
+                       .display_with(LeadingZeros::All)
+                       .with_subseconds(SubsecondStyle::Groups(2))
+                       .to_string(),
+                   "00:00.000'000");
+
+        let zero = ZeroStyle::Zero("0s");
+        assert_eq!(DecomposedTime::zero().display_with(LeadingZeros::All).with_zero_style(zero)
+                       .to_string(),
+                   "0s");
+        assert_eq!(time.display_with(LeadingZeros::All).with_zero_style(zero).to_string(),
+                   "00:05.000'050");
+    }
+
+    #[test]
+    fn test_display_min_unit() {
+        let time = DecomposedTime::new(2, 182, 5, 7, 9, 0.012_345, true);
+        let display = |unit| time.display_with(LeadingZeros::All).with_min_unit(unit).to_string();
+        assert_eq!(display(Unit::Years), "2yr");
+        assert_eq!(display(Unit::Days), "2yr 182d");
+        assert_eq!(display(Unit::Hours), "2yr 182d 5h");
+        assert_eq!(display(Unit::Minutes), "2yr 182d 05:07");
+        assert_eq!(display(Unit::Seconds), "2yr 182d 05:07:09");
+        assert_eq!(display(Unit::Milliseconds), "2yr 182d 05:07:09.012");
+        assert_eq!(display(Unit::Nanoseconds), time.to_string());
+
+        let short = DecomposedTime::new(0, 0, 3, 0, 0, 0.0, false);
+        let display = |unit| short.display_with(LeadingZeros::All).with_min_unit(unit).to_string();
+        assert_eq!(display(Unit::Days), "-0d");
+        assert_eq!(display(Unit::Hours), "-3h");
+        assert_eq!(display(Unit::Minutes), "-03:00");
+    }
+
+    #[test]
+    fn test_from_display_str() {
+        assert_eq!(DecomposedTime::from_display_str("1yr 00:00:00.000'000'010"),
+                   Ok(DecomposedTime::default()
+                          .with_years(1)
+                          .with_fractional_seconds(0.00000001)));
+        assert_eq!("00:30.500'000'100".parse::<DecomposedTime>().unwrap().subsec_nanoseconds(),
+                   500_000_100);
+
+        let time = DecomposedTime::new(2, 182, 12, 0, 0, 0.0, false);
+        assert_eq!(time.to_string().parse(), Ok(time));
+
+        assert_eq!(DecomposedTime::from_display_str("1yr 00:00"), Err(ParseDisplayError));
+        assert_eq!(DecomposedTime::from_display_str("00:60"), Err(ParseDisplayError));
+        assert_eq!(DecomposedTime::from_display_str("1:00"), Err(ParseDisplayError));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_test::{assert_tokens, Configure, Token};
+
+        let time = DecomposedTime::from_secs_nanos(2 * 86_400 + 5, 0, true)
+            .with_fractional_seconds(0.25);
+        assert_tokens(&time.clone().readable(), &[Token::Str("2d 00:00:05.250")]);
+        assert_tokens(&time.compact(),
+                      &[Token::Tuple { len: 3 },
+                        Token::U64(2 * 86400 + 5),
+                        Token::U32(250_000_000),
+                        Token::I8(1),
+                        Token::TupleEnd]);
+
+        let negative = DecomposedTime::from_secs_nanos(90, 500_000_000, false);
+        assert_tokens(&negative.readable(), &[Token::Str("-01:30.500")]);
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn test_prost() {
+        let duration = prost_types::Duration {
+            seconds: -5400,
+            nanos: -250_000_000,
+        };
+        let time = DecomposedTime::from(duration);
+        assert!(time.is_negative());
+        assert_eq!((time.hours(), time.minutes(), time.milliseconds()), (1, 30, 250));
+        assert_eq!(prost_types::Duration::try_from(time), Ok(duration));
+
+        let mixed = prost_types::Duration {
+            seconds: 2,
+            nanos: -500_000_000,
+        };
+        assert_eq!(mixed.decompose().unwrap().to_string(), "00:01.500");
+
+        let too_long = DecomposedTime::zero().with_years(u64::MAX);
+        assert_eq!(prost_types::Duration::try_from(too_long),
+                   Err(FormatError::ValueOutOfRange));
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_rkyv() {
+        use rkyv::rancor::Error;
+
+        let time = DecomposedTime::from_secs_nanos(3 * 31_536_000 + 12, 500_000_000, false);
+        let bytes = rkyv::to_bytes::<Error>(&time).unwrap();
+        let archived = rkyv::access::<ArchivedDecomposedTime, Error>(&bytes).unwrap();
+        assert_eq!(archived.years(), time.years());
+        assert_eq!(archived.subsec_nanoseconds(), time.subsec_nanoseconds());
+        assert_eq!(archived.signum(), -1);
+        assert_eq!(rkyv::deserialize::<DecomposedTime, Error>(archived).unwrap(),
+                   time);
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_js_millis() {
+        let time = Millis(90_250.0).decompose().unwrap();
+        assert_eq!(time.minutes(), 1);
+        assert_eq!(time.seconds(), 30);
+        assert_eq!(time.milliseconds(), 250);
+    }
+
+    #[test]
+    fn test_const_zero() {
+        const ZERO: DecomposedTime = DecomposedTime::zero();
+        assert_eq!(ZERO, DecomposedTime::default());
+        assert_eq!(ZERO.total_nanoseconds(), Some(0));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        let delta = chrono::TimeDelta::seconds(-3725) - chrono::TimeDelta::nanoseconds(1);
+        let time = delta.decompose().unwrap();
+        assert!(time.is_negative());
+        assert_eq!((time.hours(), time.minutes(), time.seconds()), (1, 2, 5));
+        assert_eq!(time.subsec_nanoseconds(), 1);
+        assert_eq!(chrono::TimeDelta::try_from(time).unwrap(), delta);
+
+        let time = DecomposedTime::default().with_years(u64::MAX);
+        assert_eq!(chrono::TimeDelta::try_from(time).unwrap_err(),
+                   FormatError::ValueOutOfRange);
+    }
+
+    #[test]
+    fn test_try_from_secs() {
+        assert_eq!(DecomposedTime::try_from_secs_f64(90.5).unwrap(),
+                   DecomposedTime::default()
+                       .with_minutes(1)
+                       .with_seconds(30)
+                       .with_fractional_seconds(0.5));
+        assert_eq!(DecomposedTime::try_from_secs_f64(-0.0).unwrap(),
+                   DecomposedTime::zero());
+        assert_eq!(DecomposedTime::try_from_secs_f64(-1.0),
+                   Err(TryFromSecsError::Negative));
+        assert_eq!(DecomposedTime::try_from_secs_f64(f64::NAN),
+                   Err(TryFromSecsError::Nan));
+        assert_eq!(DecomposedTime::try_from_secs_f64(f64::INFINITY),
+                   Err(TryFromSecsError::Overflow));
+        assert_eq!(DecomposedTime::try_from_secs_f32(1.5).unwrap().milliseconds(),
+                   500);
+        assert_eq!(DecomposedTime::try_from_secs_f32(f32::MAX),
+                   Err(TryFromSecsError::Overflow));
+    }
+
+    #[test]
+    fn test_builder() {
+        assert_eq!(DecomposedTime::builder()
+                       .hours(2)
+                       .minutes(90)
+                       .seconds(5)
+                       .build()
+                       .unwrap(),
+                   DecomposedTime::default()
+                       .with_hours(3)
+                       .with_minutes(30)
+                       .with_seconds(5));
+
+        let time = DecomposedTime::builder()
+            .negative(true)
+            .days(400)
+            .milliseconds(1500)
+            .nanoseconds(7)
+            .build()
+            .unwrap();
+        assert!(time.is_negative());
+        assert_eq!((time.years(), time.days(), time.seconds()), (1, 35, 1));
+        assert_eq!(time.subsec_nanoseconds(), 500_000_007);
+
+        assert_eq!(DecomposedTime::builder().years(u64::MAX).days(365).build(),
+                   Err(FormatError::ValueOutOfRange));
+    }
+
+    #[test]
+    fn test_setters_and_fields() {
+        let mut time = DecomposedTime::zero();
+        time.set_years(2);
+        time.set_hours(5);
+        time.set_seconds(9);
+        time.set_milliseconds(250);
+        time.set_nanoseconds(3);
+        time.set_negative(true);
+
+        assert!(time.is_negative());
+        assert_eq!(time.subsec_nanoseconds(), 250_000_003);
+        assert_eq!(time.fractional_seconds(), 0.250_000_003);
+        assert_eq!(time.fields().collect::<Vec<_>>(),
+                   vec![(Unit::Years, 2),
+                        (Unit::Days, 0),
+                        (Unit::Hours, 5),
+                        (Unit::Minutes, 0),
+                        (Unit::Seconds, 9),
+                        (Unit::Milliseconds, 250),
+                        (Unit::Microseconds, 0),
+                        (Unit::Nanoseconds, 3)]);
+    }
+
+    #[test]
+    fn test_nearest_half_hour() {
+        let half_hour = |hours, minutes, seconds| {
+            DecomposedTime::new(0, 0, hours, minutes, seconds, 0.0, true).nearest_half_hour()
+        };
+        assert_eq!(half_hour(0, 0, 0), (12, 0));
+        assert_eq!(half_hour(2, 30, 0), (2, 30));
+        assert_eq!(half_hour(2, 44, 59), (2, 30));
+        assert_eq!(half_hour(2, 45, 0), (3, 0));
+        assert_eq!(half_hour(13, 10, 0), (1, 0));
+        assert_eq!(half_hour(23, 50, 0), (12, 0));
+    }
+
+    #[test]
+    fn test_is_normalized() {
+        assert!(DecomposedTime::new(1, 364, 23, 59, 59, 0.999, true).is_normalized());
+        assert!(!DecomposedTime::new(0, 0, 0, 75, 0, 0.0, true).is_normalized());
+        assert!(!DecomposedTime::new(0, 0, 0, 0, 0, 1.5, true).is_normalized());
+        assert!(!DecomposedTime::new(0, 0, 0, 0, 0, f64::NAN, true).is_normalized());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let time = DecomposedTime::new(1, 2, 3, 4, 5, 0.123_456_789, true);
+        assert_eq!(time.clone().truncated(Unit::Milliseconds),
+                   DecomposedTime::new(1, 2, 3, 4, 5, 0.123, true));
+        assert_eq!(time.clone().truncated(Unit::Hours),
+                   DecomposedTime::new(1, 2, 3, 0, 0, 0.0, true));
+        assert_eq!(time.clone().truncated(Unit::Nanoseconds), time);
+    }
+
+    #[test]
+    fn test_as_kv() {
+        let time = DecomposedTime::zero().with_hours(3).with_minutes(4);
+        let kv = time.as_kv();
+        assert_eq!(kv[0], ("years", 0));
+        assert_eq!(kv[2], ("hours", 3));
+        assert_eq!(kv[3], ("minutes", 4));
+        assert_eq!(kv[7], ("nanoseconds", 0));
+    }
+
+    #[test]
+    fn test_generic_accessors() {
+        let time = DecomposedTime::zero()
+            .try_with(Unit::Years, 40_000)
+            .and_then(|time| time.try_with(Unit::Hours, 23))
+            .and_then(|time| time.try_with(Unit::Microseconds, 999))
+            .unwrap();
+        for &(unit, value) in &[(Unit::Years, 40_000), (Unit::Hours, 23),
+                                (Unit::Microseconds, 999), (Unit::Seconds, 0)] {
+            assert_eq!(time.get(unit), value);
+        }
+        assert_eq!(time.subsec_nanoseconds(), 999_000);
+
+        assert_eq!(time.clone().try_with(Unit::Days, 365),
+                   Err(FormatError::ValueOutOfRange));
+        assert_eq!(time.clone().try_with(Unit::Nanoseconds, u64::MAX),
+                   Err(FormatError::ValueOutOfRange));
+        assert_eq!(time.get(Unit::Months), 0);
+        assert_eq!(time.try_with(Unit::Months, 1), Err(FormatError::UnknownField));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_setter_out_of_bounds() {
+        DecomposedTime::zero().set_microseconds(1000);
+    }
+
+    #[test]
+    fn test_quantize() {
+        let minute = time::Duration::from_secs(60);
+        let time = time::Duration::from_secs(90);
+        assert_eq!(quantize(time, minute, Rounding::Nearest).unwrap().to_string(), "02:00");
+        assert_eq!(quantize(time, minute, Rounding::TowardZero).unwrap().to_string(), "01:00");
+        assert_eq!(quantize(time::Duration::from_secs(61), minute, Rounding::AwayFromZero)
+                       .unwrap()
+                       .to_string(),
+                   "02:00");
+        assert_eq!(quantize(time::Duration::from_secs(89), minute, Rounding::Nearest)
+                       .unwrap()
+                       .to_string(),
+                   "01:00");
+
+        let negative = DecomposedTime::new(0, 0, 0, 7, 40, 0.0, false);
+        assert_eq!(quantize(negative, time::Duration::from_secs(15 * 60), Rounding::default())
+                       .unwrap()
+                       .to_string(),
+                   "-15:00");
+        let step = time::Duration::from_millis(5);
+        assert_eq!(quantize(time::Duration::from_millis(1_234), step, Rounding::Nearest)
+                       .unwrap()
+                       .to_string(),
+                   "00:01.235");
+        assert_eq!(quantize(time, time::Duration::from_secs(0), Rounding::Nearest),
+                   Err(FormatError::ValueOutOfRange));
+    }
+
+    #[test]
+    fn test_debug() {
+        let time = DecomposedTime::new(0, 0, 1, 30, 0, 0.5, false);
+        assert_eq!(format!("{:?}", time),
+                   "DecomposedTime { value: -01:30:00.500, sign: -1, \
+                    total_nanoseconds: 5400500000000, years: 0, days: 0, hours: 1, \
+                    minutes: 30, seconds: 0, milliseconds: 500, microseconds: 0, \
+                    nanoseconds: 0 }");
+        assert!(format!("{:#?}", time).contains("\n    total_nanoseconds: 5400500000000,\n"));
+    }
+
     #[test]
     fn test_display() {
-        assert_eq!(format!("{}", FloatDuration::years(2.5).decompose().unwrap()),
-                   "2yr 182d 12:00:00");
-        assert_eq!(format!("{}", FloatDuration::years(1.0).decompose().unwrap()),
-                   "1yr 00:00:00");
-        assert_eq!(format!("{}", FloatDuration::days(2.0).decompose().unwrap()),
-                   "2d 00:00:00");
-        assert_eq!(format!("{}", FloatDuration::minutes(10.0).decompose().unwrap()),
-                   "10:00");
-        assert_eq!(format!("{}", FloatDuration::microseconds(50.0).decompose().unwrap()),
-                   "00:00.000'050");
-        assert_eq!(format!("{}", FloatDuration::seconds(12.5).decompose().unwrap()),
-                   "00:12.500");
-        assert_eq!(format!("{}",
-                           FloatDuration::milliseconds(100.0).decompose().unwrap()),
-                   "00:00.100");
-        assert_eq!(format!("{}", FloatDuration::nanoseconds(10.0).decompose().unwrap()),
-                   "00:00.000'000'010");
-
-        assert_eq!(format!("{}",
-                           (FloatDuration::days(10.0) + FloatDuration::minutes(20.0) +
-                            FloatDuration::seconds(2.0))
-                                   .decompose()
-                                   .unwrap()),
-                   "10d 00:20:02");
-        assert_eq!(format!("{}",
-                           (FloatDuration::seconds(30.5) + FloatDuration::nanoseconds(100.0))
-                               .decompose()
-                               .unwrap()),
-                   "00:30.500'000'100");
-        assert_eq!(format!("{}",
-                           (FloatDuration::seconds(90.0) + FloatDuration::microseconds(500.0))
-                               .decompose()
-                               .unwrap()),
-                   "01:30.000'500");
+        let display = |secs, nanos| DecomposedTime::from_secs_nanos(secs, nanos, true).to_string();
+        assert_eq!(display(2 * 31_536_000 + 182 * 86_400 + 12 * 3600, 0), "2yr 182d 12:00:00");
+        assert_eq!(display(31_536_000, 0), "1yr 00:00:00");
+        assert_eq!(display(2 * 86_400, 0), "2d 00:00:00");
+        assert_eq!(display(600, 0), "10:00");
+        assert_eq!(display(0, 50_000), "00:00.000'050");
+        assert_eq!(display(12, 500_000_000), "00:12.500");
+        assert_eq!(display(0, 100_000_000), "00:00.100");
+        assert_eq!(display(0, 10), "00:00.000'000'010");
 
+        assert_eq!(display(10 * 86_400 + 20 * 60 + 2, 0), "10d 00:20:02");
+        assert_eq!(display(30, 500_000_100), "00:30.500'000'100");
+        assert_eq!(display(90, 500_000), "01:30.000'500");
+        assert_eq!(DecomposedTime::from_secs_nanos(12, 500_000_000, false).to_string(),
+                   "-00:12.500");
     }
 }
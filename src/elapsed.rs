@@ -0,0 +1,49 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use decomposed::Decompose;
+use fmt::SiDuration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Elapsed {
+    duration: Duration,
+}
+
+impl Elapsed {
+    pub fn since(start: Instant) -> Elapsed {
+        Elapsed { duration: start.elapsed() }
+    }
+    pub fn from_duration(duration: Duration) -> Elapsed {
+        Elapsed { duration }
+    }
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+impl From<Duration> for Elapsed {
+    fn from(duration: Duration) -> Elapsed {
+        Elapsed::from_duration(duration)
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let time = self.duration.decompose().map_err(|_| fmt::Error)?;
+        fmt::Display::fmt(&SiDuration::new(time), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Elapsed::from_duration(Duration::from_micros(3200)).to_string(),
+                   "3.2ms");
+        assert_eq!(format!("{:.1}", Elapsed::from_duration(Duration::from_millis(1520))),
+                   "1.5s");
+        assert!(Elapsed::since(Instant::now()).to_string().ends_with('s'));
+    }
+}
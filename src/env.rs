@@ -0,0 +1,227 @@
+use std::env::{self, VarError};
+use std::error;
+use std::fmt;
+use std::time::Duration;
+
+use humanize::{scaled_decimal, Abbreviations};
+use units::{NANOS_PER_DAY, NANOS_PER_HOUR, NANOS_PER_MINUTE, NANOS_PER_SEC, NANOS_PER_WEEK,
+            NANOS_PER_YEAR};
+
+// Why `parse_var` couldn't read a duration, naming the variable so the message can be shown to
+// whoever set it. `part` is the piece of `value` that didn't parse, such as "75" in "1:75".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvError {
+    NotPresent(String),
+    NotUnicode(String),
+    Invalid {
+        name: String,
+        value: String,
+        part: String,
+    },
+}
+
+// Reads the environment variable `name` as a duration in any form `parse` accepts.
+pub fn parse_var(name: &str) -> Result<Duration, EnvError> {
+    let value = env::var(name).map_err(|e| match e {
+                                           VarError::NotPresent => {
+                                               EnvError::NotPresent(name.to_owned())
+                                           }
+                                           VarError::NotUnicode(_) => {
+                                               EnvError::NotUnicode(name.to_owned())
+                                           }
+                                       })?;
+    let part = match parse_part(&value) {
+        Ok(duration) => return Ok(duration),
+        Err(part) => part.to_owned(),
+    };
+    Err(EnvError::Invalid {
+            name: name.to_owned(),
+            value,
+            part,
+        })
+}
+
+// Accepts the spellings durations usually get in configuration: units as `Abbreviations` reads
+// them ("90s", "1h30m", "1.5 d"), a clock ("1:30:00", "05:00", "0:00:01.5") and ISO 8601
+// ("PT1H30M", "P1DT12H"). ISO months have no fixed length and are rejected, as are negative
+// durations and bare numbers, whose unit would be a guess.
+pub fn parse(text: &str) -> Option<Duration> {
+    parse_part(text).ok()
+}
+
+// Like `parse`, failing with the part of `text` that couldn't be read.
+fn parse_part(text: &str) -> Result<Duration, &str> {
+    let text = text.trim();
+    let nanos = if text.starts_with('P') {
+        parse_iso(text)?
+    } else if text.contains(':') {
+        parse_clock(text)?
+    } else {
+        return parse_units(text);
+    };
+    let secs = nanos / NANOS_PER_SEC as u128;
+    if secs > u64::MAX as u128 {
+        return Err(text);
+    }
+    Ok(Duration::new(secs as u64, (nanos % NANOS_PER_SEC as u128) as u32))
+}
+
+// Units as `Abbreviations` reads them. On failure each count and its unit ("1.5 d") is tried
+// alone to find the one at fault; if they all parse, the total was too large.
+fn parse_units(text: &str) -> Result<Duration, &str> {
+    let abbreviations = Abbreviations::new();
+    if let Ok(duration) = abbreviations.parse(text) {
+        return Ok(duration);
+    }
+    let mut rest = text;
+    while !rest.is_empty() {
+        let number_len = rest.find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+            .unwrap_or(rest.len());
+        let after = rest[number_len..].trim_start();
+        let unit_len = after.find(|ch: char| !ch.is_alphabetic()).unwrap_or(after.len());
+        let part = &rest[..rest.len() - after.len() + unit_len];
+        if part.is_empty() {
+            return Err(&rest[..rest.find(char::is_whitespace).unwrap_or(rest.len())]);
+        }
+        if abbreviations.parse(part).is_err() {
+            return Err(part);
+        }
+        rest = rest[part.len()..].trim_start_matches(|ch: char| ch.is_whitespace() || ch == ',');
+    }
+    Err(text)
+}
+
+// `[H:]M:S[.fff]`, with the fields after the first below 60.
+fn parse_clock(text: &str) -> Result<u128, &str> {
+    let fields: Vec<&str> = text.split(':').collect();
+    if fields.len() > 3 {
+        return Err(text);
+    }
+    let (&last, rest) = fields.split_last().ok_or(text)?;
+    let scales = [NANOS_PER_MINUTE, NANOS_PER_HOUR];
+    let mut nanos = clock_field(last, NANOS_PER_SEC)?;
+    if !rest.is_empty() && nanos >= 60 * NANOS_PER_SEC as u128 {
+        return Err(last);
+    }
+    for (i, (&field, &scale)) in rest.iter().rev().zip(scales.iter()).enumerate() {
+        let value = clock_field(field, scale)?;
+        if field.contains('.') || (i + 1 < rest.len() && value >= 60 * scale as u128) {
+            return Err(field);
+        }
+        nanos = nanos.checked_add(value).ok_or(text)?;
+    }
+    Ok(nanos)
+}
+
+// Only digits and a point, since `scaled_decimal` would also take "+5" from `str::parse`.
+fn clock_field(field: &str, scale: u64) -> Result<u128, &str> {
+    if !field.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return Err(field);
+    }
+    scaled_decimal(field, scale).ok_or(field)
+}
+
+// An ISO 8601 duration, `P[nY][nW][nD][T[nH][nM][nS]]`.
+fn parse_iso(text: &str) -> Result<u128, &str> {
+    let (date, time) = match text.find('T') {
+        Some(t) => (&text[1..t], Some(&text[t + 1..])),
+        None => (&text[1..], None),
+    };
+    let date_units = [('Y', NANOS_PER_YEAR), ('W', NANOS_PER_WEEK), ('D', NANOS_PER_DAY)];
+    let time_units = [('H', NANOS_PER_HOUR), ('M', NANOS_PER_MINUTE), ('S', NANOS_PER_SEC)];
+
+    let mut nanos = iso_part(date, &date_units)?;
+    if let Some(time) = time {
+        if time.is_empty() {
+            return Err(text);
+        }
+        nanos = nanos.checked_add(iso_part(time, &time_units)?).ok_or(text)?;
+    }
+    if date.is_empty() && time.is_none() {
+        return Err(text);
+    }
+    Ok(nanos)
+}
+
+// Numbers each followed by one of `units`, in the order given.
+fn iso_part<'a>(mut text: &'a str, units: &[(char, u64)]) -> Result<u128, &'a str> {
+    let mut nanos: u128 = 0;
+    let mut units = units.iter();
+    while !text.is_empty() {
+        let end = text.find(|ch: char| !ch.is_ascii_digit() && ch != '.').ok_or(text)?;
+        let designator = text[end..].chars().next().ok_or(text)?;
+        let part = &text[..end + designator.len_utf8()];
+        let &(_, scale) = units.by_ref().find(|&&(unit, _)| unit == designator).ok_or(part)?;
+        let value = scaled_decimal(&text[..end], scale).ok_or(part)?;
+        nanos = nanos.checked_add(value).ok_or(part)?;
+        text = &text[part.len()..];
+    }
+    Ok(nanos)
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EnvError::NotPresent(ref name) => write!(f, "environment variable {} is not set", name),
+            EnvError::NotUnicode(ref name) => {
+                write!(f, "environment variable {} is not valid unicode", name)
+            }
+            EnvError::Invalid { ref name, ref value, ref part } => {
+                write!(f,
+                       "environment variable {}={:?} is not a duration, {:?} is invalid \
+                        (expected e.g. 30s, 1h30m, 01:30:00 or PT1H30M)",
+                       name,
+                       value,
+                       part)
+            }
+        }
+    }
+}
+
+impl error::Error for EnvError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let secs = |secs: u64| Some(Duration::from_secs(secs));
+        assert_eq!(parse("90s"), secs(90));
+        assert_eq!(parse(" 1h30m "), secs(5400));
+        assert_eq!(parse("1:30:00"), secs(5400));
+        assert_eq!(parse("05:00"), secs(300));
+        assert_eq!(parse("0:00:01.5"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse("36:00:00"), secs(129_600));
+        assert_eq!(parse("PT1H30M"), secs(5400));
+        assert_eq!(parse("P1DT12H"), secs(129_600));
+        assert_eq!(parse("P2W"), secs(1_209_600));
+        assert_eq!(parse("PT0.25S"), Some(Duration::from_millis(250)));
+
+        for bad in &["30", "", "-5s", "1:75", "1:2:3:4", "P1M", "PT", "P", "PT1S1H", "1h:30",
+                     "+1:30", "1:+30"] {
+            assert_eq!(parse(bad), None, "{:?}", bad);
+        }
+
+        let parts = [("soon", "soon"), ("1h 30x", "30x"), ("2h -5s", "-5s"), ("30", "30"),
+                     ("1:75", "75"), ("+1:30", "+1"), ("1:2:3:4", "1:2:3:4"), ("P1M", "1M"),
+                     ("P1DT2S3H", "3H"), ("PT", "PT")];
+        for &(bad, part) in &parts {
+            assert_eq!(parse_part(bad), Err(part), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_parse_var() {
+        env::set_var("DURATION_FMT_TEST_TIMEOUT", "2m30s");
+        assert_eq!(parse_var("DURATION_FMT_TEST_TIMEOUT"), Ok(Duration::from_secs(150)));
+
+        env::set_var("DURATION_FMT_TEST_BAD", "1:75");
+        let err = parse_var("DURATION_FMT_TEST_BAD").unwrap_err();
+        assert_eq!(err.to_string(),
+                   "environment variable DURATION_FMT_TEST_BAD=\"1:75\" is not a duration, \
+                    \"75\" is invalid (expected e.g. 30s, 1h30m, 01:30:00 or PT1H30M)");
+        assert_eq!(parse_var("DURATION_FMT_TEST_UNSET"),
+                   Err(EnvError::NotPresent("DURATION_FMT_TEST_UNSET".to_owned())));
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::{self, Write};
+
+use decomposed::Decompose;
+use super::{make_format, FormatError, NumberFormatter};
+
+// SGR parameters ("1", "2;37", "38;5;208") for each part of a colored duration. Unit words are
+// the alphabetic runs of the format's literal text and separators the other non-blank ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Theme {
+    value: &'static str,
+    unit: &'static str,
+    separator: &'static str,
+    negative: &'static str,
+}
+
+// Bright numbers, plain units, dim separators, and red numbers when the duration is negative.
+pub fn format_colored<D>(format: &str, time: D, theme: &Theme) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let format = make_format(format, time)?;
+    let painter = Painter {
+        theme,
+        negative: format.time().is_negative(),
+    };
+    let mut colored = String::new();
+    write!(colored, "{}", format.with_numbers(&painter))?;
+    Ok(colored)
+}
+
+impl Theme {
+    pub const fn new() -> Theme {
+        Theme {
+            value: "1",
+            unit: "",
+            separator: "2",
+            negative: "1;31",
+        }
+    }
+    #[must_use]
+    pub fn with_value(mut self, sgr: &'static str) -> Theme {
+        self.value = sgr;
+        self
+    }
+    #[must_use]
+    pub fn with_unit(mut self, sgr: &'static str) -> Theme {
+        self.unit = sgr;
+        self
+    }
+    #[must_use]
+    pub fn with_separator(mut self, sgr: &'static str) -> Theme {
+        self.separator = sgr;
+        self
+    }
+    #[must_use]
+    pub fn with_negative(mut self, sgr: &'static str) -> Theme {
+        self.negative = sgr;
+        self
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::new()
+    }
+}
+
+struct Painter<'a> {
+    theme: &'a Theme,
+    negative: bool,
+}
+
+impl<'a> NumberFormatter for Painter<'a> {
+    fn write_number(&self, f: &mut dyn fmt::Write, _: char, text: &str) -> fmt::Result {
+        let sgr = if self.negative { self.theme.negative } else { self.theme.value };
+        paint(f, sgr, text)
+    }
+
+    fn write_literal(&self, f: &mut dyn fmt::Write, text: &str) -> fmt::Result {
+        let style = |ch: char| if ch.is_alphabetic() {
+            self.theme.unit
+        } else if ch.is_whitespace() {
+            ""
+        } else {
+            self.theme.separator
+        };
+
+        let mut start = 0;
+        let mut chars = text.char_indices().peekable();
+        while let Some((_, ch)) = chars.next() {
+            let end = chars.peek().map_or(text.len(), |&(end, _)| end);
+            if !matches!(chars.peek(), Some(&(_, next)) if style(next) == style(ch)) {
+                paint(f, style(ch), &text[start..end])?;
+                start = end;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn paint(f: &mut dyn fmt::Write, sgr: &str, text: &str) -> fmt::Result {
+    if sgr.is_empty() || text.is_empty() {
+        return f.write_str(text);
+    }
+    write!(f, "\u{1b}[{}m{}\u{1b}[0m", sgr, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decomposed::DecomposedTime;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_colored() {
+        assert_eq!(format_colored("%H:%M", Duration::from_secs(3900), &Theme::new()).unwrap(),
+                   "\u{1b}[1m01\u{1b}[0m\u{1b}[2m:\u{1b}[0m\u{1b}[1m05\u{1b}[0m");
+
+        let negative = DecomposedTime::new(0, 0, 0, 2, 0, 0.0, false);
+        assert_eq!(format_colored("%m min", negative, &Theme::new()).unwrap(),
+                   "\u{1b}[1;31m2\u{1b}[0m min");
+
+        let theme = Theme::new().with_value("").with_unit("36").with_separator("");
+        assert_eq!(format_colored("%s secs, %x ms.", Duration::from_millis(4_250), &theme)
+                       .unwrap(),
+                   "4 \u{1b}[36msecs\u{1b}[0m, 250 \u{1b}[36mms\u{1b}[0m.");
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+
+use super::{FormatSpec, FIELD_DELIMITER};
+
+// Builds a format string from typed pieces, so formats made in code can't fail with
+// `UnknownField`: `Format::new().hours_padded().literal(":").minutes_padded()` is "%H:%M".
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Format {
+    format: String,
+}
+
+impl Format {
+    pub fn new() -> Format {
+        Format::default()
+    }
+
+    // Copied as-is; a '%' in `text` is escaped.
+    pub fn literal(mut self, text: &str) -> Format {
+        for ch in text.chars() {
+            if ch == FIELD_DELIMITER {
+                self.format.push(FIELD_DELIMITER);
+            }
+            self.format.push(ch);
+        }
+        self
+    }
+
+    pub fn years(self) -> Format {
+        self.field('Y')
+    }
+    pub fn days(self) -> Format {
+        self.field('D')
+    }
+    pub fn hours(self) -> Format {
+        self.field('h')
+    }
+    pub fn minutes(self) -> Format {
+        self.field('m')
+    }
+    pub fn seconds(self) -> Format {
+        self.field('s')
+    }
+    pub fn hours_padded(self) -> Format {
+        self.field('H')
+    }
+    pub fn minutes_padded(self) -> Format {
+        self.field('M')
+    }
+    pub fn seconds_padded(self) -> Format {
+        self.field('S')
+    }
+    pub fn milliseconds(self) -> Format {
+        self.field('x')
+    }
+    pub fn microseconds(self) -> Format {
+        self.field('y')
+    }
+    pub fn nanoseconds(self) -> Format {
+        self.field('z')
+    }
+    pub fn fractional_seconds(self) -> Format {
+        self.field('f')
+    }
+    pub fn total_hours(self) -> Format {
+        self.field('T')
+    }
+    pub fn total_days(self) -> Format {
+        self.field('U')
+    }
+
+    // `%<width>Y` and `%<width>D`; the width must be 1 to 9.
+    pub fn years_padded(self, width: u8) -> Format {
+        self.padded_field('Y', width)
+    }
+    pub fn days_padded(self, width: u8) -> Format {
+        self.padded_field('D', width)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.format
+    }
+    pub fn into_string(self) -> String {
+        self.format
+    }
+
+    // A spec for the built format, borrowing its string.
+    pub fn build(&self) -> FormatSpec<'_> {
+        FormatSpec::new(&self.format).expect("built formats are always valid")
+    }
+
+    fn field(mut self, field: char) -> Format {
+        self.format.push(FIELD_DELIMITER);
+        self.format.push(field);
+        self
+    }
+
+    fn padded_field(mut self, field: char, width: u8) -> Format {
+        assert!((1..=9).contains(&width), "field width must be 1 to 9");
+        self.format.push(FIELD_DELIMITER);
+        self.format.push((b'0' + width) as char);
+        self.format.push(field);
+        self
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_builder() {
+        let format = Format::new()
+            .hours_padded()
+            .literal(":")
+            .minutes_padded()
+            .literal(":")
+            .seconds_padded()
+            .literal(".")
+            .milliseconds();
+        assert_eq!(format.as_str(), "%H:%M:%S.%x");
+        let time = Duration::from_millis(3_723_040);
+        assert_eq!(format.build().with_time(time).unwrap().to_string(), "01:02:03.040");
+
+        let format = Format::new().days_padded(3).literal(" days, 100% ").hours().literal("h");
+        assert_eq!(format.to_string(), "%3D days, 100%% %hh");
+        assert_eq!(format.build().with_time(time).unwrap().to_string(), "000 days, 100% 1h");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_width() {
+        Format::new().days_padded(10);
+    }
+}
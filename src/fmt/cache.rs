@@ -0,0 +1,203 @@
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+
+use decomposed::{Decompose, DecomposedTime};
+use super::{DurationFormat, FormatError, FormatSpec};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    is_negative: bool,
+    years: u64,
+    days: u32,
+    hours: u32,
+    minutes: u32,
+    seconds: u32,
+}
+
+#[derive(Clone, Debug)]
+struct CacheEntry {
+    rendered: String,
+    last_used: u64,
+}
+
+// Durations are truncated to whole seconds, both for the lookup key and for the rendered
+// output, so subsecond fields in the spec always render as zero. `recency` maps each entry's
+// last use to its key, so the least recently used one is found without scanning `entries`.
+#[derive(Clone, Debug)]
+pub struct FormatCache<'a> {
+    spec: FormatSpec<'a>,
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<CacheKey, CacheEntry>,
+    recency: BTreeMap<u64, CacheKey>,
+}
+
+impl<'a> FormatCache<'a> {
+    pub fn new(spec: FormatSpec<'a>, capacity: usize) -> FormatCache<'a> {
+        assert!(capacity > 0, "cache capacity must be non-zero");
+        FormatCache {
+            spec,
+            capacity,
+            tick: 0,
+            entries: HashMap::with_capacity(capacity),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    pub fn spec(&self) -> FormatSpec<'a> {
+        self.spec
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    pub fn format<D>(&mut self, time: D) -> Result<&str, FormatError>
+        where D: Decompose
+    {
+        let time = time.decompose()
+            .map_err(|e| FormatError::DecomposeError(e.into()))?
+            .with_fractional_seconds(0.0);
+        let key = CacheKey {
+            is_negative: time.is_negative(),
+            years: time.years(),
+            days: time.days(),
+            hours: time.hours(),
+            minutes: time.minutes(),
+            seconds: time.seconds(),
+        };
+        self.tick += 1;
+
+        if !self.entries.contains_key(&key) {
+            let rendered = self.render(time)?;
+            if self.entries.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            self.entries.insert(key,
+                                CacheEntry {
+                                    rendered,
+                                    last_used: 0,
+                                });
+        }
+
+        let entry = self.entries.get_mut(&key).expect("entry was just inserted");
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(self.tick, key);
+        entry.last_used = self.tick;
+        Ok(&entry.rendered)
+    }
+
+    fn render(&self, time: DecomposedTime) -> Result<String, FormatError> {
+        let mut rendered = String::new();
+        write!(rendered, "{}", self.spec.with_time(time)?)?;
+        Ok(rendered)
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        if let Some((_, key)) = self.recency.pop_first() {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+// A `DurationFormat` that keeps its text after the first `Display` and reuses it until
+// `set_time` changes the time, for UIs that redraw unchanged values every frame. Unlike
+// `DurationFormat` it can't be shared between threads.
+#[derive(Clone, Debug)]
+pub struct CachedFormat<'a> {
+    format: DurationFormat<'a>,
+    text: OnceCell<String>,
+}
+
+impl<'a> CachedFormat<'a> {
+    pub fn new(format: DurationFormat<'a>) -> CachedFormat<'a> {
+        CachedFormat {
+            format,
+            text: OnceCell::new(),
+        }
+    }
+
+    pub fn format(&self) -> &DurationFormat<'a> {
+        &self.format
+    }
+    pub fn into_inner(self) -> DurationFormat<'a> {
+        self.format
+    }
+    pub fn is_cached(&self) -> bool {
+        self.text.get().is_some()
+    }
+
+    // Replaces the time as `DurationFormat::set_time` does. The cached text is only dropped if
+    // the time actually changed.
+    pub fn set_time<D>(&mut self, time: D) -> Result<(), FormatError>
+        where D: Decompose
+    {
+        let old = self.format.time().clone();
+        self.format.set_time(time)?;
+        if *self.format.time() != old {
+            self.text.take();
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for CachedFormat<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.text.get_or_init(|| self.format.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cache() {
+        let spec = FormatSpec::new("%H:%M:%S.%x").unwrap();
+        let mut cache = FormatCache::new(spec, 2);
+
+        assert_eq!(cache.format(Duration::from_millis(61_500)).unwrap(),
+                   "00:01:01.000");
+        assert_eq!(cache.format(Duration::from_millis(61_900)).unwrap(),
+                   "00:01:01.000");
+        assert_eq!(cache.len(), 1);
+
+        cache.format(Duration::from_secs(3600)).unwrap();
+        cache.format(Duration::from_secs(61)).unwrap();
+        cache.format(Duration::from_secs(7200)).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.entries.values().any(|e| e.rendered == "00:01:01.000"));
+        assert!(cache.entries.values().all(|e| e.rendered != "01:00:00.000"));
+    }
+
+    #[test]
+    fn test_cache_eviction() {
+        let spec = FormatSpec::new("%M:%S").unwrap();
+        let mut cache = FormatCache::new(spec, 3);
+        for secs in 1..4 {
+            cache.format(Duration::from_secs(secs)).unwrap();
+        }
+        cache.format(Duration::from_secs(1)).unwrap();
+        assert_eq!(cache.len(), 3);
+
+        cache.format(Duration::from_secs(4)).unwrap();
+        let mut cached: Vec<&str> = cache.entries.values().map(|e| &e.rendered[..]).collect();
+        cached.sort();
+        assert_eq!(cached, ["00:01", "00:03", "00:04"]);
+        assert_eq!(cache.recency.len(), 3);
+
+        cache.format(Duration::from_secs(5)).unwrap();
+        assert!(cache.entries.values().all(|e| e.rendered != "00:03"));
+    }
+}
@@ -0,0 +1,224 @@
+use std::fmt;
+
+use decomposed::{Decompose, DecomposedTime};
+use units::Unit;
+use super::{FormatError, UnitSpacing};
+
+// Renders a duration with unit letters glued to the numbers ("2h30m4s"), for URLs and CLI
+// flags. With the default hours-to-nanoseconds range the output parses with Go's
+// `time.ParseDuration` and with humantime; anything above the largest unit is folded into it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactDuration {
+    time: DecomposedTime,
+    largest: Unit,
+    smallest: Unit,
+    skip_zero: bool,
+    semantics: Semantics,
+    spacing: UnitSpacing,
+}
+
+// How the sign of a compact duration is shown. `Elapsed` reads the duration as time since an
+// event ("5m ago", or "in 5m" when negative) and `Remaining` as a countdown ("5m left", or
+// "5m overdue" once the deadline has passed). Only `CompactDuration` reads it: `Display`, the SI
+// and humanize formatters and format strings keep their own sign handling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Semantics {
+    #[default]
+    Signed,
+    Elapsed,
+    Remaining,
+}
+
+pub fn format_compact<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    Ok(CompactDuration::new(time).to_string())
+}
+
+// Chat-friendly preset: the clock-face emoji nearest to the duration followed by its compact
+// form to the minute, e.g. "🕝 2h30m".
+pub fn format_clock_emoji<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let (hour, minute) = time.nearest_half_hour();
+    let first_face = if minute == 0 { 0x1f550 } else { 0x1f55c };
+    let face = ::std::char::from_u32(first_face + hour - 1).expect("clock faces are contiguous");
+
+    let text = CompactDuration::new(time).with_units(Unit::Hours, Unit::Minutes);
+    Ok(format!("{} {}", face, text))
+}
+
+impl CompactDuration {
+    pub fn new(time: DecomposedTime) -> CompactDuration {
+        CompactDuration {
+            time,
+            largest: Unit::Hours,
+            smallest: Unit::Nanoseconds,
+            skip_zero: true,
+            semantics: Semantics::Signed,
+            spacing: UnitSpacing::None,
+        }
+    }
+    #[must_use]
+    pub fn with_units(mut self, largest: Unit, smallest: Unit) -> CompactDuration {
+        assert!(largest >= smallest, "largest unit is smaller than the smallest unit");
+        self.largest = largest;
+        self.smallest = smallest;
+        self
+    }
+    #[must_use]
+    pub fn with_skip_zero(mut self, skip_zero: bool) -> CompactDuration {
+        self.skip_zero = skip_zero;
+        self
+    }
+    #[must_use]
+    pub fn with_semantics(mut self, semantics: Semantics) -> CompactDuration {
+        self.semantics = semantics;
+        self
+    }
+    // With any spacing but `None` the parts are also split by a space, "2 h 30 m".
+    #[must_use]
+    pub fn with_spacing(mut self, spacing: UnitSpacing) -> CompactDuration {
+        self.spacing = spacing;
+        self
+    }
+    pub fn semantics(&self) -> Semantics {
+        self.semantics
+    }
+    pub fn spacing(&self) -> UnitSpacing {
+        self.spacing
+    }
+    pub fn time(&self) -> &DecomposedTime {
+        &self.time
+    }
+}
+
+impl From<DecomposedTime> for CompactDuration {
+    fn from(time: DecomposedTime) -> CompactDuration {
+        CompactDuration::new(time)
+    }
+}
+
+impl fmt::Display for CompactDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut rem = self.time.total_seconds() * Unit::Seconds.nanoseconds() as u128 +
+                      self.time.subsec_nanoseconds() as u128;
+        let units = Unit::ALL
+            .iter()
+            .cloned()
+            .filter(|&unit| unit <= self.largest && unit >= self.smallest);
+
+        let mut parts = Vec::new();
+        for unit in units {
+            let scale = unit.nanoseconds() as u128;
+            let value = rem / scale;
+            rem %= scale;
+            if value > 0 || !self.skip_zero {
+                parts.push((value, unit));
+            }
+        }
+        let spacing = self.spacing.as_str();
+        let is_zero = parts.iter().all(|&(value, _)| value == 0);
+        let negative = self.time.is_negative() && !is_zero;
+
+        match (self.semantics, negative) {
+            (Semantics::Signed, true) => f.write_str("-")?,
+            (Semantics::Elapsed, true) => f.write_str("in ")?,
+            _ => (),
+        }
+        if is_zero {
+            // Go writes a zero duration as "0s"; keep to the smallest unit if seconds are excluded.
+            let unit = if self.smallest <= Unit::Seconds && self.largest >= Unit::Seconds {
+                Unit::Seconds
+            } else {
+                self.smallest
+            };
+            write!(f, "0{}{}", spacing, ascii_symbol(unit))?;
+        } else {
+            for (i, (value, unit)) in parts.into_iter().enumerate() {
+                if i > 0 && !spacing.is_empty() {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{}{}{}", value, spacing, ascii_symbol(unit))?;
+            }
+        }
+        match (self.semantics, negative) {
+            (Semantics::Elapsed, false) => f.write_str(" ago"),
+            (Semantics::Remaining, false) => f.write_str(" left"),
+            (Semantics::Remaining, true) => f.write_str(" overdue"),
+            _ => Ok(()),
+        }
+    }
+}
+
+// "us" rather than "µs", so the output stays plain ASCII.
+fn ascii_symbol(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Microseconds => "us",
+        _ => unit.symbol(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_compact() {
+        let time = Duration::from_secs(2 * 3600 + 30 * 60 + 4);
+        assert_eq!(format_compact(time).unwrap(), "2h30m4s");
+        assert_eq!(format_compact(Duration::from_secs(2 * 86_400)).unwrap(), "48h");
+        assert_eq!(format_compact(Duration::from_micros(1500)).unwrap(), "1ms500us");
+        let negative = DecomposedTime::from_secs_nanos(90, 0, false);
+        assert_eq!(format_compact(negative).unwrap(), "-1m30s");
+        assert_eq!(format_compact(Duration::from_secs(0)).unwrap(), "0s");
+
+        let time = DecomposedTime::from_secs_nanos(400 * 86_400 + 4, 250_000_000, true);
+        assert_eq!(CompactDuration::new(time.clone())
+                       .with_units(Unit::Years, Unit::Seconds)
+                       .to_string(),
+                   "1y35d4s");
+        assert_eq!(CompactDuration::new(time)
+                       .with_units(Unit::Days, Unit::Minutes)
+                       .with_skip_zero(false)
+                       .to_string(),
+                   "400d0h0m");
+        assert_eq!(format_clock_emoji(Duration::from_secs(9000)).unwrap(), "🕝 2h30m");
+        assert_eq!(format_clock_emoji(Duration::from_secs(59 * 60)).unwrap(), "🕐 59m");
+        assert_eq!(format_clock_emoji(Duration::from_secs(20)).unwrap(), "🕛 0m");
+        assert_eq!(CompactDuration::new(DecomposedTime::zero())
+                       .with_units(Unit::Milliseconds, Unit::Nanoseconds)
+                       .to_string(),
+                   "0ns");
+    }
+
+    #[test]
+    fn test_semantics() {
+        let ahead = CompactDuration::new(DecomposedTime::from_secs_nanos(300, 0, true));
+        let behind = CompactDuration::new(DecomposedTime::from_secs_nanos(300, 0, false));
+        assert_eq!(behind.to_string(), "-5m");
+        assert_eq!(ahead.clone().with_semantics(Semantics::Elapsed).to_string(), "5m ago");
+        assert_eq!(behind.clone().with_semantics(Semantics::Elapsed).to_string(), "in 5m");
+        assert_eq!(ahead.with_semantics(Semantics::Remaining).to_string(), "5m left");
+        assert_eq!(behind.with_semantics(Semantics::Remaining).to_string(), "5m overdue");
+
+        let zero = CompactDuration::new(DecomposedTime::zero());
+        assert_eq!(zero.with_semantics(Semantics::Remaining).to_string(), "0s left");
+    }
+
+    #[test]
+    fn test_spacing() {
+        let time = DecomposedTime::from_secs_nanos(9000, 0, true);
+        let compact = CompactDuration::new(time);
+        assert_eq!(compact.clone().with_spacing(UnitSpacing::Space).to_string(), "2 h 30 m");
+        assert_eq!(compact.with_spacing(UnitSpacing::NarrowNoBreak).to_string(),
+                   "2\u{202f}h 30\u{202f}m");
+        assert_eq!(CompactDuration::new(DecomposedTime::zero())
+                       .with_spacing(UnitSpacing::Space)
+                       .to_string(),
+                   "0 s");
+    }
+}
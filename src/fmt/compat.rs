@@ -0,0 +1,129 @@
+use super::{FormatError, FIELD_DELIMITER};
+
+// Translates a moment.js / dayjs duration template into this crate's format syntax. Each unit
+// renders its value within the next larger unit (`HH` is hours of the day), unlike
+// moment-duration-format's "largest token absorbs the remainder" trimming.
+pub fn from_moment(template: &str) -> Result<String, FormatError> {
+    let mut format = String::with_capacity(template.len() * 2);
+    let mut chars = template.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '[' {
+            let mut closed = false;
+            for literal in chars.by_ref() {
+                if literal == ']' {
+                    closed = true;
+                    break;
+                }
+                push_literal(&mut format, literal);
+            }
+            if !closed {
+                return Err(FormatError::UnexpectedFieldDelimiter);
+            }
+            continue;
+        }
+
+        if !ch.is_ascii_alphabetic() {
+            push_literal(&mut format, ch);
+            continue;
+        }
+
+        let mut len = 1;
+        while chars.peek() == Some(&ch) {
+            chars.next();
+            len += 1;
+        }
+        let field = match (ch, len) {
+            ('Y', 1) | ('y', 1) => 'Y',
+            ('D', 1) | ('d', 1) => 'D',
+            ('H', 1) | ('h', 1) => 'h',
+            ('H', 2) | ('h', 2) => 'H',
+            ('m', 1) => 'm',
+            ('m', 2) => 'M',
+            ('s', 1) => 's',
+            ('s', 2) => 'S',
+            ('S', 3) => 'x',
+            _ => return Err(FormatError::UnknownField),
+        };
+        format.push(FIELD_DELIMITER);
+        format.push(field);
+    }
+    Ok(format)
+}
+
+// Translates the duration-like subset of a strftime / chrono format. `%j` becomes the zero-padded
+// day count; unlike strftime's day of the year it starts at zero. Date codes (`%Y`, `%m`, `%d`,
+// weekday and month names, ...) have no duration equivalent and fail with `UnknownField`.
+pub fn from_strftime(template: &str) -> Result<String, FormatError> {
+    let mut format = String::with_capacity(template.len() * 2);
+    let mut chars = template.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != FIELD_DELIMITER {
+            push_literal(&mut format, ch);
+            continue;
+        }
+        let code = chars.next().ok_or(FormatError::UnexpectedFieldDelimiter)?;
+        let code = match code {
+            '3' | '6' | '9' => {
+                if chars.next() != Some('f') {
+                    return Err(FormatError::UnknownField);
+                }
+                match code {
+                    '3' => "%x",
+                    '6' => "%x%y",
+                    _ => "%x%y%z",
+                }
+            }
+            'H' => "%H",
+            'M' => "%M",
+            'S' => "%S",
+            'j' => "%3D",
+            'f' => "%x%y%z",
+            'T' => "%H:%M:%S",
+            'R' => "%H:%M",
+            'n' => "\n",
+            't' => "\t",
+            '%' => "%%",
+            _ => return Err(FormatError::UnknownField),
+        };
+        format.push_str(code);
+    }
+    Ok(format)
+}
+
+fn push_literal(format: &mut String, ch: char) {
+    if ch == FIELD_DELIMITER {
+        format.push(FIELD_DELIMITER);
+    }
+    format.push(ch);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_moment() {
+        assert_eq!(from_moment("HH:mm:ss").unwrap(), "%H:%M:%S");
+        assert_eq!(from_moment("d [days], h:mm:ss.SSS").unwrap(),
+                   "%D days, %h:%M:%S.%x");
+        assert_eq!(from_moment("s [100%]").unwrap(), "%s 100%%");
+        assert_eq!(from_moment("M [months]").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(from_moment("DD:HH").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(from_moment("h [hours").unwrap_err(),
+                   FormatError::UnexpectedFieldDelimiter);
+    }
+
+    #[test]
+    fn test_from_strftime() {
+        assert_eq!(from_strftime("%H:%M:%S").unwrap(), "%H:%M:%S");
+        assert_eq!(from_strftime("%j days %T.%3f").unwrap(), "%3D days %H:%M:%S.%x");
+        assert_eq!(from_strftime("%R (100%%)%n").unwrap(), "%H:%M (100%%)\n");
+        assert_eq!(from_strftime("%S.%f").unwrap(), "%S.%x%y%z");
+        assert_eq!(from_strftime("%Y-%m-%d").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(from_strftime("%a %H").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(from_strftime("%3S").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(from_strftime("%H%").unwrap_err(), FormatError::UnexpectedFieldDelimiter);
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+
+use decomposed::{Decompose, DecomposedTime};
+use super::FormatError;
+use units::NANOS_PER_DAY;
+
+const NANOS_PER_DECIMAL_SECOND: u128 = NANOS_PER_DAY as u128 / 100_000;
+
+// French Revolutionary decimal time: days of 10 hours, hours of 100 minutes and minutes of 100
+// seconds, so a decimal second is 0.864 s. Renders as "2d 3:45:67"; a precision (`{:.2}`)
+// adds that many decimals of the decimal second.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecimalTime {
+    time: DecomposedTime,
+}
+
+pub fn format_decimal<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    Ok(DecimalTime::new(time).to_string())
+}
+
+impl DecimalTime {
+    pub fn new(time: DecomposedTime) -> DecimalTime {
+        DecimalTime { time }
+    }
+    pub fn time(&self) -> &DecomposedTime {
+        &self.time
+    }
+
+    pub fn days(&self) -> u128 {
+        self.total_decimal_seconds() / 100_000
+    }
+    pub fn hours(&self) -> u32 {
+        (self.total_decimal_seconds() / 10_000 % 10) as u32
+    }
+    pub fn minutes(&self) -> u32 {
+        (self.total_decimal_seconds() / 100 % 100) as u32
+    }
+    pub fn seconds(&self) -> u32 {
+        (self.total_decimal_seconds() % 100) as u32
+    }
+    // The fraction of a decimal second below `seconds`.
+    pub fn fractional_seconds(&self) -> f64 {
+        (self.total_nanoseconds() % NANOS_PER_DECIMAL_SECOND) as f64 /
+        NANOS_PER_DECIMAL_SECOND as f64
+    }
+
+    fn total_nanoseconds(&self) -> u128 {
+        self.time.total_seconds() * 1_000_000_000 + self.time.subsec_nanoseconds() as u128
+    }
+    fn total_decimal_seconds(&self) -> u128 {
+        self.total_nanoseconds() / NANOS_PER_DECIMAL_SECOND
+    }
+}
+
+impl From<DecomposedTime> for DecimalTime {
+    fn from(time: DecomposedTime) -> DecimalTime {
+        DecimalTime::new(time)
+    }
+}
+
+impl fmt::Display for DecimalTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.time.is_negative() && self.total_nanoseconds() > 0 {
+            f.write_str("-")?;
+        }
+        if self.days() > 0 {
+            write!(f, "{}d ", self.days())?;
+        }
+        write!(f, "{}:{:02}:{:02}", self.hours(), self.minutes(), self.seconds())?;
+
+        if let Some(precision) = f.precision().filter(|&precision| precision > 0) {
+            f.write_str(".")?;
+            let mut rem = self.total_nanoseconds() % NANOS_PER_DECIMAL_SECOND;
+            for _ in 0..precision {
+                rem *= 10;
+                write!(f, "{}", rem / NANOS_PER_DECIMAL_SECOND)?;
+                rem %= NANOS_PER_DECIMAL_SECOND;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_decimal() {
+        assert_eq!(format_decimal(Duration::from_secs(43_200)).unwrap(), "5:00:00");
+        assert_eq!(format_decimal(Duration::from_secs(86_400 * 2 + 3_600)).unwrap(),
+                   "2d 0:41:66");
+        assert_eq!(format_decimal(Duration::from_secs(0)).unwrap(), "0:00:00");
+
+        let time = DecimalTime::new(Duration::from_millis(1_296).decompose().unwrap());
+        assert_eq!((time.hours(), time.minutes(), time.seconds()), (0, 0, 1));
+        assert_eq!(time.fractional_seconds(), 0.5);
+        assert_eq!(format!("{:.2}", time), "0:00:01.50");
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use decomposed::Decompose;
+use super::FormatSpec;
+
+// The format string of a `DurationField`, picked by type so the field stores nothing but the
+// duration. String const generics aren't stable, so each format is a marker type:
+// `struct Hm; impl FieldFormat for Hm { const FORMAT: &'static str = "%H:%M"; }`.
+pub trait FieldFormat {
+    const FORMAT: &'static str;
+}
+
+// "%H:%M:%S"
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClockFormat;
+
+// "%M:%S.%x"
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StopwatchFormat;
+
+// A struct field holding a duration that renders through `F::FORMAT` in both `Display` and
+// `Debug`, so `#[derive(Debug)]` on the containing type shows `elapsed: "00:01:05"` without a
+// manual impl. `Debug` quotes the text like a string, as `Formatted` does.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DurationField<D, F = ClockFormat> {
+    time: D,
+    format: PhantomData<F>,
+}
+
+impl FieldFormat for ClockFormat {
+    const FORMAT: &'static str = "%H:%M:%S";
+}
+
+impl FieldFormat for StopwatchFormat {
+    const FORMAT: &'static str = "%M:%S.%x";
+}
+
+impl<D, F> DurationField<D, F>
+    where F: FieldFormat
+{
+    pub const fn new(time: D) -> DurationField<D, F> {
+        DurationField {
+            time,
+            format: PhantomData,
+        }
+    }
+    pub fn get(&self) -> &D {
+        &self.time
+    }
+    pub fn get_mut(&mut self) -> &mut D {
+        &mut self.time
+    }
+    pub fn into_inner(self) -> D {
+        self.time
+    }
+}
+
+impl<D, F> DurationField<D, F>
+    where D: Decompose + Clone,
+          F: FieldFormat
+{
+    const SPEC: FormatSpec<'static> = match FormatSpec::new(F::FORMAT) {
+        Ok(spec) => spec,
+        Err(_) => panic!("FieldFormat::FORMAT is not a valid format string"),
+    };
+}
+
+impl<D, F> From<D> for DurationField<D, F>
+    where F: FieldFormat
+{
+    fn from(time: D) -> DurationField<D, F> {
+        DurationField::new(time)
+    }
+}
+
+impl<D, F> fmt::Display for DurationField<D, F>
+    where D: Decompose + Clone,
+          F: FieldFormat
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Self::SPEC.display(f, self.time.clone())
+    }
+}
+
+impl<D, F> fmt::Debug for DurationField<D, F>
+    where D: Decompose + Clone,
+          F: FieldFormat
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_duration_field() {
+        struct HoursMinutes;
+        impl FieldFormat for HoursMinutes {
+            const FORMAT: &'static str = "%Hh%M";
+        }
+
+        #[derive(Debug)]
+        struct Lap {
+            total: DurationField<Duration>,
+            split: DurationField<Duration, StopwatchFormat>,
+            shift: DurationField<Duration, HoursMinutes>,
+        }
+
+        let lap = Lap {
+            total: DurationField::new(Duration::from_secs(65)),
+            split: Duration::from_millis(4_250).into(),
+            shift: DurationField::new(Duration::from_secs(8 * 3600 + 1800)),
+        };
+        assert_eq!(format!("{:?}", lap),
+                   r#"Lap { total: "00:01:05", split: "00:04.250", shift: "08h30" }"#);
+        assert_eq!(lap.split.to_string(), "00:04.250");
+        assert_eq!(lap.shift.to_string(), "08h30");
+        assert_eq!(lap.total.into_inner(), Duration::from_secs(65));
+    }
+}
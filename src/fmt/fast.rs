@@ -0,0 +1,130 @@
+use std::fmt;
+
+use itoa;
+use ryu;
+
+const ZEROS: &str = "00000000000000000000";
+
+pub fn write_int<T>(f: &mut dyn fmt::Write, value: T, width: usize) -> fmt::Result
+    where T: itoa::Integer
+{
+    let mut buffer = itoa::Buffer::new();
+    let digits = buffer.format(value);
+
+    if digits.len() < width {
+        f.write_str(&ZEROS[..width - digits.len()])?;
+    }
+    f.write_str(digits)
+}
+
+// ryu switches to exponent notation and keeps a trailing ".0" where `Display` for f64 does
+// neither, so those cases are adjusted or handed back to `Display`.
+pub fn write_float(f: &mut dyn fmt::Write, value: f64) -> fmt::Result {
+    if !value.is_finite() {
+        return write!(f, "{}", value);
+    }
+
+    let mut buffer = ryu::Buffer::new();
+    let repr = buffer.format_finite(value);
+
+    if repr.contains('e') {
+        write!(f, "{}", value)
+    } else if let Some(integral) = repr.strip_suffix(".0") {
+        f.write_str(integral)
+    } else {
+        f.write_str(repr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    #[cfg(feature = "bench")]
+    use std::hint;
+    #[cfg(feature = "bench")]
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    #[cfg(feature = "bench")]
+    use bench;
+    #[cfg(feature = "bench")]
+    use decomposed::DecomposedTime;
+
+    struct Int(u128, usize);
+    struct Float(f64);
+
+    impl fmt::Display for Int {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_int(f, self.0, self.1)
+        }
+    }
+    impl fmt::Display for Float {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_float(f, self.0)
+        }
+    }
+
+    #[test]
+    fn test_matches_display() {
+        for &value in &[0, 5, 42, 999, 1000, 123456789, u64::MAX as u128, u128::MAX] {
+            for &width in &[0, 2, 3] {
+                assert_eq!(Int(value, width).to_string(),
+                           format!("{:01$}", value, width));
+            }
+        }
+        for &value in &[0.0, 0.5, 0.1 + 0.2, 0.00001, 0.0000001, 0.999999999, 1.0, 1.0e17] {
+            assert_eq!(Float(value).to_string(), format!("{}", value));
+        }
+    }
+
+    // The `write!` calls the fast path replaces, as the formatter makes them without fast-fmt.
+    #[cfg(feature = "bench")]
+    fn display_int<T>(f: &mut dyn fmt::Write, value: T, width: usize) -> fmt::Result
+        where T: fmt::Display
+    {
+        write!(f, "{:01$}", value, width)
+    }
+    #[cfg(feature = "bench")]
+    fn display_float(f: &mut dyn fmt::Write, value: f64) -> fmt::Result {
+        write!(f, "{}", value)
+    }
+
+    // Writes the clock fields, total hours and kiloyears of every duration `rounds` times.
+    #[cfg(feature = "bench")]
+    fn write_fields(workload: &[DecomposedTime],
+                    rounds: u32,
+                    clock: fn(&mut dyn fmt::Write, u32, usize) -> fmt::Result,
+                    total: fn(&mut dyn fmt::Write, u128, usize) -> fmt::Result,
+                    float: fn(&mut dyn fmt::Write, f64) -> fmt::Result)
+                    -> Duration {
+        let mut buffer = String::new();
+        let start = Instant::now();
+        for _ in 0..rounds {
+            for time in workload {
+                buffer.clear();
+                clock(&mut buffer, time.hours(), 2).unwrap();
+                clock(&mut buffer, time.minutes(), 2).unwrap();
+                clock(&mut buffer, time.seconds(), 2).unwrap();
+                total(&mut buffer, time.total_hours(), 0).unwrap();
+                float(&mut buffer, time.years() as f64 / 1e3).unwrap();
+                hint::black_box(&buffer);
+            }
+        }
+        start.elapsed()
+    }
+
+    // Times the fast writers against `write!` on the `bench::durations` workload. Run with
+    // `cargo test --release --features fast-fmt,bench -- --ignored --nocapture`, next to
+    // `bench_formats` with and without fast-fmt for the whole formatter.
+    #[cfg(feature = "bench")]
+    #[test]
+    #[ignore]
+    fn bench_against_write() {
+        let workload = bench::durations(1, 10_000);
+        let fast = write_fields(&workload, 20, write_int, write_int, write_float);
+        let display = write_fields(&workload, 20, display_int, display_int, display_float);
+        println!("fields: {:.2}x faster than write!",
+                 display.as_secs_f64() / fast.as_secs_f64());
+        assert!(fast < display);
+    }
+}
@@ -0,0 +1,93 @@
+use decomposed::DecomposedTime;
+use super::FormatSpec;
+
+// A format string field for documentation and format-builder UIs. `example` is what the field
+// renders for `EXAMPLE_TIME`, 1yr 2d 03:04:05.678'901'234.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FieldDescriptor {
+    pub code: &'static str,
+    pub description: &'static str,
+    pub example: &'static str,
+}
+
+pub const EXAMPLE_TIME: &str = "1yr 2d 03:04:05.678'901'234";
+
+// Every field `validate_format` accepts. `%<n>D`/`%<n>Y`, `%(<field>%<n>)` and `%{<preset>}`
+// are listed with one representative width, cycle and preset each.
+pub const SUPPORTED_FIELDS: &[FieldDescriptor] = &[
+    field("%Y", "years", "1"),
+    field("%D", "days, after whole years", "2"),
+    field("%H", "hours, zero-padded", "03"),
+    field("%M", "minutes, zero-padded", "04"),
+    field("%S", "seconds, zero-padded", "05"),
+    field("%h", "hours", "3"),
+    field("%m", "minutes", "4"),
+    field("%s", "seconds", "5"),
+    field("%x", "milliseconds, zero-padded to 3 digits", "678"),
+    field("%y", "microseconds after the milliseconds, zero-padded to 3 digits", "901"),
+    field("%z", "nanoseconds after the microseconds, zero-padded to 3 digits", "234"),
+    field("%f", "fraction of a second", "0.678901234"),
+    field("%F", "fraction of a second to 5 decimals", "0.67890"),
+    field("%T", "total hours", "8811"),
+    field("%U", "total days", "367"),
+    field("%K", "years in thousands", "0.001"),
+    field("%G", "years in billions", "0.000000001"),
+    field("%@", ".beats of 86.4 seconds", "367127.84"),
+    field("%3D", "days, zero-padded to the given width (1-9)", "002"),
+    field("%3f", "fraction of a second to the given number of decimals (1-9)", "0.678"),
+    field("%(T%24)", "a field modulo a cycle length", "3"),
+    field("%{clock}", "a preset format: clock, compact, decimal or si", EXAMPLE_TIME),
+    field("%%", "a literal '%'", "%"),
+];
+
+impl<'a> FormatSpec<'a> {
+    // This spec rendered for `EXAMPLE_TIME`, so settings UIs can preview a format without
+    // choosing a duration of their own.
+    pub fn example(&self) -> String {
+        let time = DecomposedTime::from_display_str(EXAMPLE_TIME).expect("EXAMPLE_TIME is valid");
+        self.with_time(time)
+            .expect("EXAMPLE_TIME is normalized and fits every field")
+            .to_string()
+    }
+}
+
+const fn field(code: &'static str,
+               description: &'static str,
+               example: &'static str)
+               -> FieldDescriptor {
+    FieldDescriptor {
+        code,
+        description,
+        example,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fmt::{format_duration, validate_format, FieldOverflow, Numerals};
+
+    #[test]
+    fn test_supported_fields() {
+        let time = DecomposedTime::from_display_str(EXAMPLE_TIME).unwrap();
+        for field in SUPPORTED_FIELDS {
+            assert_eq!(validate_format(field.code), Ok(()));
+            assert_eq!(format_duration(field.code, time.clone()).unwrap(),
+                       field.example,
+                       "{}",
+                       field.code);
+        }
+    }
+
+    #[test]
+    fn test_example() {
+        let spec = FormatSpec::new("%D days, %H:%M:%S.%x").unwrap();
+        assert_eq!(spec.example(), "2 days, 03:04:05.678");
+        assert_eq!(spec.with_field_overflow(FieldOverflow::Error)
+                       .with_numerals(Numerals::Thai)
+                       .example(),
+                   "๒ days, ๐๓:๐๔:๐๕.๖๗๘");
+        assert_eq!(FormatSpec::new("%{compact}").unwrap().example(),
+                   "8811h4m5s678ms901us234ns");
+    }
+}
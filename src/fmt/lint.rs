@@ -0,0 +1,149 @@
+use std::fmt;
+
+use super::{is_width_digit, FIELD_DELIMITER};
+
+// Patterns that are valid but probably not what the author of a format string meant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LintWarning {
+    // `%S` with no `%M`: the minutes are silently dropped.
+    SecondsWithoutMinutes,
+    // A subsecond field that follows the seconds with no '.' in between, as in "%S%x".
+    SubsecondsWithoutDecimalPoint,
+    // The same field appears more than once.
+    DuplicateField(char),
+    // `%f`/`%F` next to `%x`/`%y`/`%z`, which repeat the same digits.
+    FractionMixedWithSubseconds,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Field(char),
+    Literal(String),
+}
+
+// Reports suspicious patterns in a format string. Meant for formats that pass
+// `validate_format`; cycle (`%(...)`) and preset (`%{...}`) fields are not checked.
+pub fn lint(format: &str) -> Vec<LintWarning> {
+    let tokens = tokenize(format);
+    let fields: Vec<char> = tokens.iter()
+        .filter_map(|token| match *token {
+                        Token::Field(field) => Some(field),
+                        Token::Literal(_) => None,
+                    })
+        .collect();
+    let has = |field: char| fields.contains(&field);
+    let mut warnings = Vec::new();
+
+    if has('S') && !has('M') {
+        warnings.push(LintWarning::SecondsWithoutMinutes);
+    }
+
+    let mut after_seconds: Option<bool> = None;
+    for token in &tokens {
+        match *token {
+            Token::Field('S') | Token::Field('s') => after_seconds = Some(false),
+            Token::Field('x') | Token::Field('y') | Token::Field('z') => {
+                if after_seconds == Some(false) {
+                    warnings.push(LintWarning::SubsecondsWithoutDecimalPoint);
+                    break;
+                }
+                after_seconds = None;
+            }
+            Token::Field(_) => after_seconds = None,
+            Token::Literal(ref text) => {
+                if text.contains('.') && after_seconds.is_some() {
+                    after_seconds = Some(true);
+                }
+            }
+        }
+    }
+
+    let mut seen = Vec::new();
+    for &field in &fields {
+        if seen.contains(&field) && !warnings.contains(&LintWarning::DuplicateField(field)) {
+            warnings.push(LintWarning::DuplicateField(field));
+        }
+        seen.push(field);
+    }
+
+    if (has('f') || has('F')) && (has('x') || has('y') || has('z')) {
+        warnings.push(LintWarning::FractionMixedWithSubseconds);
+    }
+    warnings
+}
+
+fn tokenize(format: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut chars = format.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != FIELD_DELIMITER {
+            literal.push(ch);
+            continue;
+        }
+        let field = match chars.next() {
+            Some(FIELD_DELIMITER) => {
+                literal.push(FIELD_DELIMITER);
+                continue;
+            }
+            Some('(') => {
+                chars.by_ref().take_while(|&ch| ch != ')').count();
+                None
+            }
+            Some('{') => {
+                chars.by_ref().take_while(|&ch| ch != '}').count();
+                None
+            }
+            Some(width) if width.is_ascii() && is_width_digit(width as u8) => chars.next(),
+            field => field,
+        };
+        if !literal.is_empty() {
+            tokens.push(Token::Literal(literal.split_off(0)));
+        }
+        if let Some(field) = field {
+            tokens.push(Token::Field(field));
+        }
+    }
+    if !literal.is_empty() {
+        tokens.push(Token::Literal(literal));
+    }
+    tokens
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LintWarning::SecondsWithoutMinutes => {
+                f.write_str("%S is used without %M, so whole minutes are not shown")
+            }
+            LintWarning::SubsecondsWithoutDecimalPoint => {
+                f.write_str("subsecond field follows the seconds without a decimal point")
+            }
+            LintWarning::DuplicateField(field) => write!(f, "%{} appears more than once", field),
+            LintWarning::FractionMixedWithSubseconds => {
+                f.write_str("%f/%F repeats the digits of %x, %y and %z")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint() {
+        assert_eq!(lint("%H:%M:%S.%x'%y'%z"), vec![]);
+        assert_eq!(lint("%S seconds"), vec![LintWarning::SecondsWithoutMinutes]);
+        assert_eq!(lint("%M:%S%x"), vec![LintWarning::SubsecondsWithoutDecimalPoint]);
+        assert_eq!(lint("%M:%S ms %x"), vec![LintWarning::SubsecondsWithoutDecimalPoint]);
+        assert_eq!(lint("%x ms"), vec![]);
+        assert_eq!(lint("%H:%M, %H hours"), vec![LintWarning::DuplicateField('H')]);
+        assert_eq!(lint("%M:%S.%x (%f)"),
+                   vec![LintWarning::FractionMixedWithSubseconds]);
+        assert_eq!(lint("%%S %(S%10) %{clock} %2D"), vec![]);
+        assert_eq!(LintWarning::DuplicateField('H').to_string(),
+                   "%H appears more than once");
+    }
+}
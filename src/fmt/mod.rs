@@ -1,17 +1,90 @@
 use std::error;
 use std::fmt::{self, Write};
+use std::str;
+use std::time::Duration;
 
-use decomposed::{Decompose, DecomposedTime};
+use decomposed::{self, Decompose, DecomposedTime};
+use units::Unit;
 
-const FIELD_DELIMITER: char = '%';
+#[cfg(feature = "ansi")]
+mod ansi;
+mod builder;
+mod cache;
+pub mod compat;
+mod compact;
+mod decimal;
+mod duration_field;
+mod fields;
+#[cfg(feature = "fast-fmt")]
+mod fast;
+mod lint;
+mod numerals;
+mod pattern;
+mod range;
+#[cfg(feature = "rayon")]
+mod par;
+mod segment;
+mod si;
+mod small;
+mod spec_buf;
+mod stream;
+mod sum;
+mod unit_decimal;
+mod width;
+
+#[cfg(feature = "fast-fmt")]
+use self::fast::{write_float, write_int};
+
+#[cfg(feature = "ansi")]
+pub use self::ansi::{format_colored, Theme};
+pub use self::builder::Format;
+pub use self::cache::{CachedFormat, FormatCache};
+pub use self::compact::{format_clock_emoji, format_compact, CompactDuration, Semantics};
+pub use self::decimal::{format_decimal, DecimalTime};
+pub use self::duration_field::{ClockFormat, DurationField, FieldFormat, StopwatchFormat};
+pub use self::fields::{FieldDescriptor, EXAMPLE_TIME, SUPPORTED_FIELDS};
+pub use self::lint::{lint, LintWarning};
+pub use self::numerals::{NumberFormatter, Numerals};
+pub use self::range::{format_range, RangeOptions};
+#[cfg(feature = "rayon")]
+pub use self::par::par_format_durations;
+pub use self::segment::format_seven_segment;
+pub use self::si::{format_si, format_uncertain, SiDuration, Uncertain};
+pub use self::small::{SmallString, INLINE_CAPACITY};
+pub use self::spec_buf::FormatSpecBuf;
+pub use self::stream::{Redraw, StreamFormatter};
+pub use self::sum::{sum_and_format, Sum};
+pub use self::unit_decimal::{as_decimal, format_fixed_seconds, format_payroll, Payroll,
+                             UnitDecimal};
+pub use self::width::WidthLimits;
+
+pub const FIELD_DELIMITER: char = '%';
+const LEFT_TO_RIGHT_ISOLATE: char = '\u{2066}';
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+const NANOS_PER_BEAT: u128 = 86_400_000_000;
+const DEFAULT_BEAT_DECIMALS: u8 = 2;
+
+// Formats that can be included in another one as `%{<name>}`, e.g. "Job ran for %{compact}".
+const PRESETS: [&str; 4] = ["clock", "compact", "decimal", "si"];
 
 #[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum FormatError {
     UnexpectedFieldDelimiter,
     UnknownField,
     FmtError,
-    DecomposeError,
+    DecomposeError(decomposed::DecomposeError),
     ValueOutOfRange,
+    // A unit abbreviation that stands for more than one unit, such as "m" for minutes and
+    // months.
+    AmbiguousUnit,
+}
+
+impl From<decomposed::DecomposeError> for FormatError {
+    fn from(err: decomposed::DecomposeError) -> FormatError {
+        FormatError::DecomposeError(err)
+    }
 }
 
 impl From<fmt::Error> for FormatError {
@@ -20,12 +93,112 @@ impl From<fmt::Error> for FormatError {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct DurationFormat<'a> {
+// Which of the zero-padded clock fields (`%H`, `%M`, `%S` and the `HH:MM:SS` part of
+// `DecomposedTime`'s `Display`) get their leading zero. `ExceptLargest` leaves the first one
+// unpadded, giving "2:05" rather than "02:05".
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LeadingZeros {
+    #[default]
+    All,
+    ExceptLargest,
+    None,
+}
+
+// How many of the `.mmm'uuu'nnn` groups `DecomposedTime`'s display writes: all three, only up
+// to the last non-zero one (the default), or a fixed count so a column of values lines up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SubsecondStyle {
+    Full,
+    #[default]
+    Trim,
+    Groups(usize),
+}
+
+// What a zero-padded field (`%H`, `%x`, `%3D`, ...) does with a value too wide for its padding,
+// such as the 125 minutes of a time built without carrying: print it wider, clamp it to the
+// largest value that fits (99), or make `with_time` fail with `ValueOutOfRange`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FieldOverflow {
+    #[default]
+    Widen,
+    Clamp,
+    Error,
+}
+
+// What happens to years and days a format has no field for: `%H:%M:%S` shows three days and five
+// seconds as "00:00:05" with `Ignore`, and as "3d 00:00:05" with `Prefix`, which writes them the
+// way `DecomposedTime`'s `Display` does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MissingLargeUnits {
+    #[default]
+    Ignore,
+    Prefix,
+}
+
+// What a zero or near-zero duration renders as. `Zero` replaces only an exactly-zero duration,
+// `Below` anything shorter than its threshold whatever the sign, so `Below(1ms, "<1ms")` and
+// `Zero("—")` cover the usual dashboard cases. Applied by format strings, `DisplayWith`,
+// `NaturalDelta` and `PreciseDelta`; plain `Display`, `CompactDuration`, `SiDuration`,
+// `describe` and `Ssml` always write the duration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ZeroStyle<'a> {
+    #[default]
+    Normal,
+    Zero(&'a str),
+    Below(Duration, &'a str),
+}
+
+// What goes between a count and its unit: "2h", "2 h", or "2 h" with a narrow no-break space
+// as SI typesetting asks for. Used by `CompactDuration` and the humanize `Labels`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UnitSpacing {
+    #[default]
+    None,
+    Space,
+    NarrowNoBreak,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatSpec<'a> {
     format: &'a str,
+    leading_zeros: LeadingZeros,
+    max_precision: Unit,
+    strict: bool,
+    numerals: Numerals,
+    bidi_isolates: bool,
+    beat_decimals: u8,
+    field_overflow: FieldOverflow,
+    missing_large_units: MissingLargeUnits,
+    zero_style: ZeroStyle<'a>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DurationFormat<'a> {
+    spec: FormatSpec<'a>,
     time: DecomposedTime,
 }
 
+// `Display` for a `DurationFormat` whose numeric fields are written by a custom
+// `NumberFormatter`.
+pub struct WithNumbers<'a, 'b: 'a> {
+    format: &'a DurationFormat<'b>,
+    numbers: &'a dyn NumberFormatter,
+}
+
+// A duration together with how to print it, for public types that want to store both and derive
+// `Debug`. Both `Display` and `Debug` render the duration; `Debug` quotes it like a string.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Formatted<'a, D>(pub FormatSpec<'a>, pub D);
+
+// A std `Duration` that prints like a `DecomposedTime`, "01:30.250", for quick debugging
+// without going through `Decompose` or a format string. `Debug` prints the same text.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pretty(pub Duration);
+
+pub fn pretty(time: Duration) -> Pretty {
+    Pretty(time)
+}
+
 pub fn format_duration<D>(format: &str, time: D) -> Result<String, FormatError>
     where D: Decompose
 {
@@ -33,122 +206,1307 @@ pub fn format_duration<D>(format: &str, time: D) -> Result<String, FormatError>
     Ok(format!("{}", fmt))
 }
 
+pub fn format_duration_small<D>(format: &str, time: D) -> Result<SmallString, FormatError>
+    where D: Decompose
+{
+    let fmt = make_format(format, time)?;
+    let mut s = SmallString::new();
+    write!(s, "{}", fmt)?;
+    Ok(s)
+}
+
+// `format_duration` with user placeholders: each `%{name}` naming one of `args` is replaced by
+// that value's `Display` output ("Task %{task} took %H:%M:%S"), while other `%{...}` fields are
+// presets as usual, so an unknown name fails the whole template with `UnknownField`. An
+// argument shadows a preset of the same name.
+pub fn format_duration_args<D>(format: &str,
+                               time: D,
+                               args: &[(&str, &dyn fmt::Display)])
+                               -> Result<String, FormatError>
+    where D: Decompose
+{
+    let mut expanded = String::with_capacity(format.len());
+    let mut rest = format;
+
+    while let Some(start) = rest.find(FIELD_DELIMITER) {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        if rest.starts_with(FIELD_DELIMITER) {
+            expanded.push_str("%%");
+            rest = &rest[1..];
+            continue;
+        }
+        expanded.push(FIELD_DELIMITER);
+        if !rest.starts_with('{') {
+            continue;
+        }
+        let end = rest.find('}').ok_or(FormatError::UnexpectedFieldDelimiter)?;
+        match args.iter().find(|&&(name, _)| name == &rest[1..end]) {
+            Some(&(_, value)) => {
+                expanded.pop();
+                let value = value.to_string();
+                expanded.push_str(&value.replace(FIELD_DELIMITER, "%%"));
+            }
+            None => expanded.push_str(&rest[..end + 1]),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    format_duration(&expanded, time)
+}
+
+// Never fails: fields that don't parse are copied through literally, and a duration that can't
+// be decomposed is written as its raw seconds. Meant for log pipelines that must always produce
+// a record, even from a slightly wrong format string.
+#[must_use]
+pub fn format_lossy<D>(format: &str, time: D) -> String
+    where D: Decompose
+{
+    let raw_seconds = time.raw_seconds();
+    match time.decompose() {
+        Ok(time) => {
+            let format = escape_invalid_fields(format);
+            format_duration(&format, time).unwrap_or(format)
+        }
+        Err(_) => {
+            raw_seconds.map_or_else(|| "<invalid duration>".to_string(),
+                                    |secs| secs.to_string())
+        }
+    }
+}
+
 pub fn make_format<'a, D>(format_str: &'a str, time: D) -> Result<DurationFormat<'a>, FormatError>
     where D: Decompose
 {
-    let decomposed = time.decompose().map_err(|_| FormatError::DecomposeError)?;
-    let fmt = DurationFormat {
-        format: format_str,
-        time: decomposed,
-    };
-    fmt.validate()?;
-    Ok(fmt)
+    FormatSpec::new(format_str)?.with_time(time)
+}
+
+pub const fn validate_format(format: &str) -> Result<(), FormatError> {
+    let bytes = format.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == FIELD_DELIMITER as u8 {
+            if i + 1 >= bytes.len() {
+                return Err(FormatError::UnexpectedFieldDelimiter);
+            }
+            if bytes[i + 1] == b'(' {
+                i = match validate_cycle_field(bytes, i + 2) {
+                    Ok(end) => end,
+                    Err(e) => return Err(e),
+                };
+                continue;
+            }
+            if bytes[i + 1] == b'{' {
+                i = match validate_preset_field(bytes, i + 2) {
+                    Ok(end) => end,
+                    Err(e) => return Err(e),
+                };
+                continue;
+            }
+            if is_width_digit(bytes[i + 1]) {
+                if i + 2 >= bytes.len() {
+                    return Err(FormatError::UnexpectedFieldDelimiter);
+                }
+                if !is_padded_field(bytes[i + 2] as char) {
+                    return Err(FormatError::UnknownField);
+                }
+                i += 3;
+                continue;
+            }
+            if !is_valid_field(bytes[i + 1] as char) {
+                return Err(FormatError::UnknownField);
+            }
+            i += 1;
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+// Checks the `<field>%<cycle>)` tail of a `%(...)` field starting at `start`, returning the
+// index just past the closing parenthesis.
+const fn validate_cycle_field(bytes: &[u8], start: usize) -> Result<usize, FormatError> {
+    if start + 1 >= bytes.len() {
+        return Err(FormatError::UnexpectedFieldDelimiter);
+    }
+    if !is_cycle_field(bytes[start] as char) || bytes[start + 1] != FIELD_DELIMITER as u8 {
+        return Err(FormatError::UnknownField);
+    }
+
+    let mut i = start + 2;
+    let mut cycle: u64 = 0;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        cycle = match cycle.checked_mul(10) {
+            Some(cycle) => match cycle.checked_add((bytes[i] - b'0') as u64) {
+                Some(cycle) => cycle,
+                None => return Err(FormatError::ValueOutOfRange),
+            },
+            None => return Err(FormatError::ValueOutOfRange),
+        };
+        i += 1;
+    }
+    if i >= bytes.len() {
+        return Err(FormatError::UnexpectedFieldDelimiter);
+    }
+    if bytes[i] != b')' || i == start + 2 {
+        return Err(FormatError::UnknownField);
+    }
+    if cycle == 0 {
+        return Err(FormatError::ValueOutOfRange);
+    }
+    Ok(i + 1)
+}
+
+// Checks the `<name>}` tail of a `%{...}` field starting at `start`, returning the index just
+// past the closing brace.
+const fn validate_preset_field(bytes: &[u8], start: usize) -> Result<usize, FormatError> {
+    let mut end = start;
+    while end < bytes.len() && bytes[end] != b'}' {
+        end += 1;
+    }
+    if end >= bytes.len() {
+        return Err(FormatError::UnexpectedFieldDelimiter);
+    }
+
+    let mut preset = 0;
+    while preset < PRESETS.len() {
+        let name = PRESETS[preset].as_bytes();
+        if name.len() == end - start {
+            let mut i = 0;
+            while i < name.len() && name[i] == bytes[start + i] {
+                i += 1;
+            }
+            if i == name.len() {
+                return Ok(end + 1);
+            }
+        }
+        preset += 1;
+    }
+    Err(FormatError::UnknownField)
+}
+
+const fn is_cycle_field(field: char) -> bool {
+    matches!(field,
+             'S' | 'M' | 'H' | 'D' | 'Y' | 'T' | 'U' | 's' | 'm' | 'h' | 'x' | 'y' | 'z')
+}
+
+fn escape_invalid_fields(format: &str) -> String {
+    let bytes = format.as_bytes();
+    let mut escaped = String::with_capacity(format.len() + 4);
+    let mut i = 0;
+
+    while let Some(offset) = format[i..].find(FIELD_DELIMITER) {
+        let start = i + offset;
+        escaped.push_str(&format[i..start]);
+
+        let end = match bytes.get(start + 1) {
+            Some(&b'(') => validate_cycle_field(bytes, start + 2).ok(),
+            Some(&b'{') => validate_preset_field(bytes, start + 2).ok(),
+            Some(&width) if is_width_digit(width) => match bytes.get(start + 2) {
+                Some(&field) if is_padded_field(field as char) => Some(start + 3),
+                _ => None,
+            },
+            Some(&field) if is_valid_field(field as char) => Some(start + 2),
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                escaped.push_str(&format[start..end]);
+                i = end;
+            }
+            None => {
+                escaped.push(FIELD_DELIMITER);
+                escaped.push(FIELD_DELIMITER);
+                i = start + 1;
+            }
+        }
+    }
+    escaped.push_str(&format[i..]);
+    escaped
+}
+
+const fn is_valid_field(field: char) -> bool {
+    matches!(field,
+             'S' | 'M' | 'H' | 'D' | 'Y' | 'K' | 'G' | 'F' | 'T' | 'U' | 's' | 'm' | 'h' | 'f' |
+             'x' | 'y' | 'z' | '@' | FIELD_DELIMITER)
+}
+
+// `%<width><field>` zero-pads the day or year count to a fixed width, e.g. `%3D` for "007", so
+// fixed-width log columns don't shift as the count grows. `%<width>f` is the fraction of a
+// second to that many decimals.
+const fn is_padded_field(field: char) -> bool {
+    matches!(field, 'D' | 'Y' | 'f')
+}
+
+const fn is_width_digit(byte: u8) -> bool {
+    matches!(byte, b'1'..=b'9')
+}
+
+impl<'a> FormatSpec<'a> {
+    pub const fn new(format: &'a str) -> Result<FormatSpec<'a>, FormatError> {
+        match validate_format(format) {
+            Ok(()) => {
+                Ok(FormatSpec {
+                       format,
+                       leading_zeros: LeadingZeros::All,
+                       max_precision: Unit::Nanoseconds,
+                       strict: false,
+                       numerals: Numerals::Latin,
+                       bidi_isolates: false,
+                       beat_decimals: DEFAULT_BEAT_DECIMALS,
+                       field_overflow: FieldOverflow::Widen,
+                       missing_large_units: MissingLargeUnits::Ignore,
+                       zero_style: ZeroStyle::Normal,
+                   })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub const fn parse_const(format: &'a str) -> FormatSpec<'a> {
+        match validate_format(format) {
+            Ok(()) => {
+                FormatSpec {
+                    format,
+                    leading_zeros: LeadingZeros::All,
+                    max_precision: Unit::Nanoseconds,
+                    strict: false,
+                    numerals: Numerals::Latin,
+                    bidi_isolates: false,
+                    beat_decimals: DEFAULT_BEAT_DECIMALS,
+                    field_overflow: FieldOverflow::Widen,
+                    missing_large_units: MissingLargeUnits::Ignore,
+                    zero_style: ZeroStyle::Normal,
+                }
+            }
+            Err(FormatError::UnexpectedFieldDelimiter) => {
+                panic!("duration format ends with an unterminated field delimiter")
+            }
+            Err(_) => panic!("duration format contains an unknown field"),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_leading_zeros(mut self, leading_zeros: LeadingZeros) -> FormatSpec<'a> {
+        self.leading_zeros = leading_zeros;
+        self
+    }
+
+    // Fields finer than `unit` render as zero, so one format string can serve both a detailed
+    // and a coarse build of the same output.
+    #[must_use]
+    pub const fn with_max_precision(mut self, unit: Unit) -> FormatSpec<'a> {
+        self.max_precision = unit;
+        self
+    }
+
+    // In strict mode a time with out-of-range fields (75 minutes, 1.5 fractional seconds) is
+    // rejected with `ValueOutOfRange` instead of being printed as-is.
+    #[must_use]
+    pub const fn with_strict(mut self, strict: bool) -> FormatSpec<'a> {
+        self.strict = strict;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_numerals(mut self, numerals: Numerals) -> FormatSpec<'a> {
+        self.numerals = numerals;
+        self
+    }
+
+    // Wraps each run of fields in Unicode directional isolates (LRI ... PDI), so the numbers keep
+    // their order when the output is embedded in right-to-left text.
+    #[must_use]
+    pub const fn with_bidi_isolates(mut self, bidi_isolates: bool) -> FormatSpec<'a> {
+        self.bidi_isolates = bidi_isolates;
+        self
+    }
+
+    // Decimals written by `%@`, the duration in .beats (Swatch Internet Time, 86.4 s each).
+    #[must_use]
+    pub const fn with_beat_decimals(mut self, decimals: u8) -> FormatSpec<'a> {
+        assert!(decimals <= 9, "beat decimals out of range");
+        self.beat_decimals = decimals;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_field_overflow(mut self, field_overflow: FieldOverflow) -> FormatSpec<'a> {
+        self.field_overflow = field_overflow;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_missing_large_units(mut self,
+                                          missing_large_units: MissingLargeUnits)
+                                          -> FormatSpec<'a> {
+        self.missing_large_units = missing_large_units;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_zero_style(mut self, zero_style: ZeroStyle<'a>) -> FormatSpec<'a> {
+        self.zero_style = zero_style;
+        self
+    }
+
+    pub const fn as_str(&self) -> &'a str {
+        self.format
+    }
+    pub const fn leading_zeros(&self) -> LeadingZeros {
+        self.leading_zeros
+    }
+    pub const fn max_precision(&self) -> Unit {
+        self.max_precision
+    }
+    pub const fn is_strict(&self) -> bool {
+        self.strict
+    }
+    pub const fn numerals(&self) -> Numerals {
+        self.numerals
+    }
+    pub const fn has_bidi_isolates(&self) -> bool {
+        self.bidi_isolates
+    }
+    pub const fn beat_decimals(&self) -> u8 {
+        self.beat_decimals
+    }
+    pub const fn field_overflow(&self) -> FieldOverflow {
+        self.field_overflow
+    }
+    pub const fn missing_large_units(&self) -> MissingLargeUnits {
+        self.missing_large_units
+    }
+    pub const fn zero_style(&self) -> ZeroStyle<'a> {
+        self.zero_style
+    }
+
+    pub fn with_time<D>(self, time: D) -> Result<DurationFormat<'a>, FormatError>
+        where D: Decompose
+    {
+        let decomposed = time.decompose()
+            .map_err(|e| FormatError::DecomposeError(e.into()))?;
+        if self.strict && !decomposed.is_normalized() {
+            return Err(FormatError::ValueOutOfRange);
+        }
+        let format = DurationFormat {
+            spec: self,
+            time: decomposed.truncated(self.max_precision),
+        };
+        // The format was validated by `new`, so a too-wide field is the only way rendering can
+        // fail; it is caught here so that `Display` never does.
+        if self.field_overflow == FieldOverflow::Error {
+            format.walk(&mut Discard, &self.numerals)?;
+        }
+        Ok(format)
+    }
+
+    // `Display` for types holding a spec and an undecomposed duration, which can't report what
+    // `with_time` would reject: a strict or `FieldOverflow::Error` spec writes the time as it
+    // is with too-wide fields clamped, and a duration that doesn't decompose is written as its
+    // raw seconds, as `format_lossy` does.
+    fn display<D>(self, f: &mut fmt::Formatter, time: D) -> fmt::Result
+        where D: Decompose
+    {
+        let raw_seconds = time.raw_seconds();
+        let time = match time.decompose() {
+            Ok(time) => time,
+            Err(_) => {
+                return match raw_seconds {
+                    Some(secs) => write!(f, "{}", secs),
+                    None => f.write_str("<invalid duration>"),
+                };
+            }
+        };
+        let field_overflow = match self.field_overflow {
+            FieldOverflow::Error => FieldOverflow::Clamp,
+            field_overflow => field_overflow,
+        };
+        let spec = self.with_strict(false).with_field_overflow(field_overflow);
+        let format = DurationFormat {
+            spec,
+            time: time.truncated(spec.max_precision),
+        };
+        format.display(f, &spec.numerals)
+    }
 }
 
 impl<'a> DurationFormat<'a> {
     pub fn format_string(&self) -> &'a str {
-        self.format
+        self.spec.as_str()
+    }
+    pub fn spec(&self) -> FormatSpec<'a> {
+        self.spec
     }
     pub fn time(&self) -> &DecomposedTime {
         &self.time
     }
 
-    fn validate(&self) -> Result<(), FormatError> {
-        let mut chars = self.format.chars();
+    // Replaces the time, applying the spec's strictness and precision as `with_time` does.
+    pub fn set_time<D>(&mut self, time: D) -> Result<(), FormatError>
+        where D: Decompose
+    {
+        self.time = self.spec.with_time(time)?.time;
+        Ok(())
+    }
 
-        while let Some(ch) = chars.next() {
-            if ch == FIELD_DELIMITER {
-                if let Some(field) = chars.next() {
-                    self.validate_field(field)?
-                } else {
-                    return Err(FormatError::UnexpectedFieldDelimiter);
-                }
-            }
+    #[must_use]
+    pub fn with_numbers<'b>(&'b self, numbers: &'b dyn NumberFormatter) -> WithNumbers<'b, 'a> {
+        WithNumbers {
+            format: self,
+            numbers,
         }
-        Ok(())
     }
 
     pub fn format(&self, f: &mut fmt::Formatter) -> Result<(), FormatError> {
-        let mut chars = self.format.chars();
+        self.format_with_numbers(f, &self.spec.numerals)
+    }
+
+    // Like `format`, with each numeric field written through `numbers` instead of the spec's
+    // numerals.
+    pub fn format_with_numbers(&self,
+                               f: &mut fmt::Formatter,
+                               numbers: &dyn NumberFormatter)
+                               -> Result<(), FormatError> {
+        self.walk(f, numbers)
+    }
+
+    // `with_time` already rejected everything `walk` can, so only an error from `f` or `numbers`
+    // is passed on and `to_string` can't panic on a format that was accepted.
+    fn display(&self, f: &mut fmt::Formatter, numbers: &dyn NumberFormatter) -> fmt::Result {
+        match self.walk(f, numbers) {
+            Err(FormatError::FmtError) => Err(fmt::Error),
+            _ => Ok(()),
+        }
+    }
+
+    fn walk(&self,
+            f: &mut dyn fmt::Write,
+            numbers: &dyn NumberFormatter)
+            -> Result<(), FormatError> {
+        let mut chars = self.format_string().chars();
+        let mut largest = true;
+        let mut isolated = false;
+        let mut out = SmallString::new();
+        let mut literal = SmallString::new();
+
+        if let Some(text) = self.spec.zero_style.replacement(&self.time) {
+            return numbers.write_literal(f, text).map_err(|e| e.into());
+        }
+        if self.spec.missing_large_units == MissingLargeUnits::Prefix {
+            self.write_missing_large_units(f, numbers)?;
+        }
 
         while let Some(ch) = chars.next() {
             if ch == FIELD_DELIMITER {
                 if let Some(field) = chars.next() {
-                    self.handle_format_field(f, field)?
+                    if field == FIELD_DELIMITER {
+                        literal.write_char(field)?;
+                        continue;
+                    }
+                    numbers.write_literal(f, &literal)?;
+                    literal.clear();
+                    if self.spec.bidi_isolates && !isolated {
+                        f.write_char(LEFT_TO_RIGHT_ISOLATE)?;
+                        isolated = true;
+                    }
+                    out.clear();
+                    let field = if field == '(' {
+                        let (field, cycle) = parse_cycle_field(&mut chars)?;
+                        self.handle_cycle_field(&mut out, field, cycle, largest)?;
+                        field
+                    } else if field == '{' {
+                        let rest = chars.as_str();
+                        let end = rest.find('}').ok_or(FormatError::UnexpectedFieldDelimiter)?;
+                        self.handle_preset_field(&mut out, &rest[..end], largest)?;
+                        chars = rest[end + 1..].chars();
+                        field
+                    } else if matches!(field, '1'..='9') {
+                        let width = field as usize - '0' as usize;
+                        let field = chars.next().ok_or(FormatError::UnexpectedFieldDelimiter)?;
+                        self.handle_padded_field(&mut out, field, width)?;
+                        field
+                    } else {
+                        self.handle_format_field(&mut out, field, largest)?;
+                        field
+                    };
+                    numbers.write_number(f, field, &out)?;
+                    largest &= !matches!(field, 'H' | 'M' | 'S');
                 } else {
                     return Err(FormatError::UnexpectedFieldDelimiter);
                 }
             } else {
-                f.write_char(ch)?
+                // ASCII punctuation between fields ("01:02:03.450") stays inside the isolate so
+                // the whole run keeps its left-to-right order; anything else ends it.
+                if isolated && !ch.is_ascii_punctuation() {
+                    numbers.write_literal(f, &literal)?;
+                    literal.clear();
+                    f.write_char(POP_DIRECTIONAL_ISOLATE)?;
+                    isolated = false;
+                }
+                literal.write_char(ch)?
             }
         }
+        numbers.write_literal(f, &literal)?;
+        if isolated {
+            f.write_char(POP_DIRECTIONAL_ISOLATE)?;
+        }
         Ok(())
     }
 
-    fn validate_field(&self, field: char) -> Result<(), FormatError> {
+    fn handle_format_field(&self,
+                           f: &mut dyn fmt::Write,
+                           field: char,
+                           largest: bool)
+                           -> Result<(), FormatError> {
+        let clock_width = self.spec.leading_zeros.width(largest);
         match field {
-            'S' | 'M' | 'H' | 'D' | 'Y' | 'F' | 'T' | 'U' | 's' | 'm' | 'h' | 'f' | 'x' | 'y' |
-            'z' => Ok(()),
+            'x' => self.write_fixed(f, self.time.milliseconds(), 3),
+            'y' => self.write_fixed(f, self.time.microseconds(), 3),
+            'z' => self.write_fixed(f, self.time.nanoseconds(), 3),
+            'f' => self.write_fraction(f, None).map_err(|e| e.into()),
+            'F' => self.write_fraction(f, Some(5)).map_err(|e| e.into()),
+            's' => write_int(f, self.time.seconds(), 0).map_err(|e| e.into()),
+            'm' => write_int(f, self.time.minutes(), 0).map_err(|e| e.into()),
+            'h' => write_int(f, self.time.hours(), 0).map_err(|e| e.into()),
+            'S' => self.write_fixed(f, self.time.seconds(), clock_width),
+            'M' => self.write_fixed(f, self.time.minutes(), clock_width),
+            'H' => self.write_fixed(f, self.time.hours(), clock_width),
+            'D' => write_int(f, self.time.days(), 0).map_err(|e| e.into()),
+            'Y' => write_int(f, self.time.years(), 0).map_err(|e| e.into()),
+            'K' => write_float(f, self.time.years() as f64 / 1e3).map_err(|e| e.into()),
+            'G' => write_float(f, self.time.years() as f64 / 1e9).map_err(|e| e.into()),
+            // Totals are u128 and can't overflow, so there is no saturating or "≥N" mode.
+            'T' => write_int(f, self.time.total_hours(), 0).map_err(|e| e.into()),
+            'U' => write_int(f, self.time.total_days(), 0).map_err(|e| e.into()),
+            '@' => self.handle_beats(f).map_err(|e| e.into()),
             _ => Err(FormatError::UnknownField),
         }
     }
 
-    fn handle_format_field(&self, f: &mut fmt::Formatter, field: char) -> Result<(), FormatError> {
+    fn write_missing_large_units(&self,
+                                 f: &mut dyn fmt::Write,
+                                 numbers: &dyn NumberFormatter)
+                                 -> Result<(), FormatError> {
+        let format = self.format_string();
+        if self.time.years() > 0 && !has_field(format, &['Y', 'K', 'G', 'T', 'U', '@']) {
+            numbers.write_number(f, 'Y', &self.time.years().to_string())?;
+            f.write_str("yr ")?;
+        }
+        if self.time.days() > 0 && !has_field(format, &['D', 'T', 'U', '@']) {
+            numbers.write_number(f, 'D', &self.time.days().to_string())?;
+            f.write_str("d ")?;
+        }
+        Ok(())
+    }
+
+    fn write_fixed<T>(&self,
+                      f: &mut dyn fmt::Write,
+                      value: T,
+                      width: usize)
+                      -> Result<(), FormatError>
+        where T: Into<u64>
+    {
+        let value = value.into();
+        let limit = 10u64.saturating_pow(width as u32);
+        let value = match self.spec.field_overflow {
+            _ if width == 0 || value < limit => value,
+            FieldOverflow::Widen => value,
+            FieldOverflow::Clamp => limit - 1,
+            FieldOverflow::Error => return Err(FormatError::ValueOutOfRange),
+        };
+        write_int(f, value, width).map_err(|e| e.into())
+    }
+
+    // The fraction of a second from the integer subsecond fields, so 0.3 s is "0.3" and never
+    // "0.30000000000000004". `None` trims trailing zeros, `Some(n)` writes exactly n decimals,
+    // truncating like the other fields do.
+    fn write_fraction(&self, f: &mut dyn fmt::Write, decimals: Option<usize>) -> fmt::Result {
+        let nanos = self.time.subsec_nanoseconds();
+        write_int(f, nanos / 1_000_000_000, 0)?;
+
+        let mut digits = SmallString::new();
+        write_int(&mut digits, nanos % 1_000_000_000, 9)?;
+        let digits = match decimals {
+            Some(decimals) => &digits[..decimals.min(9)],
+            None => digits.trim_end_matches('0'),
+        };
+        if !digits.is_empty() {
+            f.write_char('.')?;
+            f.write_str(digits)?;
+        }
+        Ok(())
+    }
+
+    fn handle_beats(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        let nanos = self.time.total_seconds() * 1_000_000_000 +
+                    self.time.subsec_nanoseconds() as u128;
+        write_int(f, nanos / NANOS_PER_BEAT, 0)?;
+        if self.spec.beat_decimals > 0 {
+            f.write_char('.')?;
+        }
+        let mut rem = nanos % NANOS_PER_BEAT;
+        for _ in 0..self.spec.beat_decimals {
+            rem *= 10;
+            write_int(f, rem / NANOS_PER_BEAT, 0)?;
+            rem %= NANOS_PER_BEAT;
+        }
+        Ok(())
+    }
+
+    fn handle_padded_field(&self,
+                           f: &mut dyn fmt::Write,
+                           field: char,
+                           width: usize)
+                           -> Result<(), FormatError> {
         match field {
-            'x' => write!(f, "{:03}", self.time.milliseconds()).map_err(|e| e.into()),
-            'y' => write!(f, "{:03}", self.time.microseconds()).map_err(|e| e.into()),
-            'z' => write!(f, "{:03}", self.time.nanoseconds()).map_err(|e| e.into()),
-            'f' => write!(f, "{}", self.time.fractional_seconds()).map_err(|e| e.into()),
-            'F' => write!(f, "{:.5}", self.time.fractional_seconds()).map_err(|e| e.into()),
-            's' => write!(f, "{}", self.time.seconds()).map_err(|e| e.into()),
-            'm' => write!(f, "{}", self.time.minutes()).map_err(|e| e.into()),
-            'h' => write!(f, "{}", self.time.hours()).map_err(|e| e.into()),
-            'S' => write!(f, "{:02}", self.time.seconds()).map_err(|e| e.into()),
-            'M' => write!(f, "{:02}", self.time.minutes()).map_err(|e| e.into()),
-            'H' => write!(f, "{:02}", self.time.hours()).map_err(|e| e.into()),
-            'D' => write!(f, "{}", self.time.days()).map_err(|e| e.into()),
-            'Y' => write!(f, "{}", self.time.years()).map_err(|e| e.into()),
-            'T' => {
-                if let Some(hours) = self.time.total_hours() {
-                    write!(f, "{}", hours).map_err(|e| e.into())
-                } else {
-                    Err(FormatError::ValueOutOfRange)
-                }
-            }
-            'U' => {
-                if let Some(hours) = self.time.total_days() {
-                    write!(f, "{}", hours).map_err(|e| e.into())
-                } else {
-                    Err(FormatError::ValueOutOfRange)
-                }
+            'D' => self.write_fixed(f, self.time.days(), width),
+            'Y' => self.write_fixed(f, self.time.years(), width),
+            'f' => self.write_fraction(f, Some(width)).map_err(|e| e.into()),
+            _ => Err(FormatError::UnknownField),
+        }
+    }
+
+    fn handle_preset_field(&self,
+                           f: &mut dyn fmt::Write,
+                           preset: &str,
+                           largest: bool)
+                           -> Result<(), FormatError> {
+        let time = self.time.clone();
+        match preset {
+            "clock" => {
+                let leading_zeros = self.spec.leading_zeros.for_clock(largest);
+                write!(f, "{}", time.display_with(leading_zeros)).map_err(|e| e.into())
             }
-            FIELD_DELIMITER => f.write_char(field).map_err(|e| e.into()),
+            "compact" => write!(f, "{}", CompactDuration::new(time)).map_err(|e| e.into()),
+            "decimal" => write!(f, "{}", DecimalTime::new(time)).map_err(|e| e.into()),
+            "si" => write!(f, "{}", SiDuration::new(time)).map_err(|e| e.into()),
             _ => Err(FormatError::UnknownField),
         }
     }
+
+    // `%(<field>%<cycle>)` renders the field modulo `cycle`, e.g. `%(T%12)` for the hour hand
+    // of an analog clock or `%(T%8)` for the hours into the current eight-hour shift.
+    fn handle_cycle_field(&self,
+                          f: &mut dyn fmt::Write,
+                          field: char,
+                          cycle: u64,
+                          largest: bool)
+                          -> Result<(), FormatError> {
+        let time = &self.time;
+        let (value, width) = match field {
+            'x' => (time.milliseconds() as u128, 3),
+            'y' => (time.microseconds() as u128, 3),
+            'z' => (time.nanoseconds() as u128, 3),
+            's' => (time.seconds() as u128, 0),
+            'm' => (time.minutes() as u128, 0),
+            'h' => (time.hours() as u128, 0),
+            'S' => (time.seconds() as u128, self.spec.leading_zeros.width(largest)),
+            'M' => (time.minutes() as u128, self.spec.leading_zeros.width(largest)),
+            'H' => (time.hours() as u128, self.spec.leading_zeros.width(largest)),
+            'D' => (time.days() as u128, 0),
+            'Y' => (time.years() as u128, 0),
+            'T' => (time.total_hours(), 0),
+            'U' => (time.total_days(), 0),
+            _ => return Err(FormatError::UnknownField),
+        };
+        write_int(f, value % cycle as u128, width).map_err(|e| e.into())
+    }
+}
+
+// Whether `format` has one of `fields`, counting padded fields by their letter. Presets render
+// the whole duration, so they count as every field; cycle fields count as none.
+fn has_field(format: &str, fields: &[char]) -> bool {
+    let mut chars = format.chars();
+    while let Some(ch) = chars.next() {
+        if ch != FIELD_DELIMITER {
+            continue;
+        }
+        let field = match chars.next() {
+            Some('{') => return true,
+            Some('(') => {
+                chars.by_ref().take_while(|&ch| ch != ')').count();
+                continue;
+            }
+            Some(width @ '1'..='9') => chars.next().unwrap_or(width),
+            Some(field) => field,
+            None => break,
+        };
+        if fields.contains(&field) {
+            return true;
+        }
+    }
+    false
+}
+
+fn parse_cycle_field(chars: &mut str::Chars) -> Result<(char, u64), FormatError> {
+    let field = chars.next().ok_or(FormatError::UnexpectedFieldDelimiter)?;
+    if !is_cycle_field(field) || chars.next() != Some(FIELD_DELIMITER) {
+        return Err(FormatError::UnknownField);
+    }
+    let rest = chars.as_str();
+    let end = rest.find(')').ok_or(FormatError::UnexpectedFieldDelimiter)?;
+    let cycle = match rest[..end].parse() {
+        Ok(0) => return Err(FormatError::ValueOutOfRange),
+        Ok(cycle) => cycle,
+        Err(_) => return Err(FormatError::UnknownField),
+    };
+    *chars = rest[end + 1..].chars();
+    Ok((field, cycle))
+}
+
+impl LeadingZeros {
+    pub(crate) fn width(self, largest: bool) -> usize {
+        match self {
+            LeadingZeros::All => 2,
+            LeadingZeros::ExceptLargest if !largest => 2,
+            LeadingZeros::ExceptLargest | LeadingZeros::None => 0,
+        }
+    }
+
+    // What `%{clock}` pads with: it has its own largest unit, which is padded too when the
+    // preset comes after an `H`, `M` or `S` field.
+    pub(crate) fn for_clock(self, largest: bool) -> LeadingZeros {
+        match self {
+            LeadingZeros::ExceptLargest if !largest => LeadingZeros::All,
+            leading_zeros => leading_zeros,
+        }
+    }
+}
+
+impl<'a> ZeroStyle<'a> {
+    // The text to write instead of `time`, if any.
+    pub fn replacement(&self, time: &DecomposedTime) -> Option<&'a str> {
+        let nanos = time.total_nanoseconds();
+        match *self {
+            ZeroStyle::Normal => None,
+            ZeroStyle::Zero(text) if nanos == Some(0) => Some(text),
+            ZeroStyle::Below(threshold, text) if matches!(nanos, Some(nanos)
+                                                           if nanos < threshold.as_nanos()) => {
+                Some(text)
+            }
+            ZeroStyle::Zero(_) | ZeroStyle::Below(..) => None,
+        }
+    }
+}
+
+impl UnitSpacing {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            UnitSpacing::None => "",
+            UnitSpacing::Space => " ",
+            UnitSpacing::NarrowNoBreak => "\u{202f}",
+        }
+    }
+}
+
+#[cfg(not(feature = "fast-fmt"))]
+fn write_int<T>(f: &mut dyn fmt::Write, value: T, width: usize) -> fmt::Result
+    where T: fmt::Display
+{
+    write!(f, "{:01$}", value, width)
+}
+
+#[cfg(not(feature = "fast-fmt"))]
+fn write_float(f: &mut dyn fmt::Write, value: f64) -> fmt::Result {
+    write!(f, "{}", value)
+}
+
+impl<'a, D> Formatted<'a, D>
+    where D: Decompose + Clone
+{
+    pub fn new(format: &'a str, time: D) -> Result<Formatted<'a, D>, FormatError> {
+        Ok(Formatted(FormatSpec::new(format)?, time))
+    }
+
+    fn render(&self) -> Result<String, FormatError> {
+        Ok(self.0.with_time(self.1.clone())?.to_string())
+    }
+}
+
+impl<'a, D> fmt::Display for Formatted<'a, D>
+    where D: Decompose + Clone
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.display(f, self.1.clone())
+    }
+}
+
+impl<'a, D> fmt::Debug for Formatted<'a, D>
+    where D: Decompose + Clone
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.render() {
+            Ok(text) => write!(f, "{:?}", text),
+            Err(e) => write!(f, "Formatted({:?}, {:?})", self.0.as_str(), e),
+        }
+    }
+}
+
+impl Pretty {
+    fn decomposed(&self) -> DecomposedTime {
+        DecomposedTime::from_secs_nanos(self.0.as_secs(), self.0.subsec_nanos(), true)
+    }
+}
+
+impl From<Duration> for Pretty {
+    fn from(time: Duration) -> Pretty {
+        Pretty(time)
+    }
+}
+
+impl fmt::Display for Pretty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.decomposed().fmt(f)
+    }
+}
+
+impl fmt::Debug for Pretty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// Swallows a rendering that is only done for its errors.
+struct Discard;
+
+impl fmt::Write for Discard {
+    fn write_str(&mut self, _: &str) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl<'a> PartialEq for DurationFormat<'a> {
+    fn eq(&self, other: &DurationFormat<'a>) -> bool {
+        self.spec == other.spec && self.time == other.time
+    }
 }
 
 impl<'a> fmt::Display for DurationFormat<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.format(f).map_err(|_| fmt::Error::default())
+        self.display(f, &self.spec.numerals)
+    }
+}
+
+impl<'a, 'b> fmt::Display for WithNumbers<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.format.display(f, self.numbers)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "float_duration")]
     use float_duration::FloatDuration;
+    use std::time::Duration;
 
     #[test]
     fn test_format() {
-        assert_eq!(format_duration("%H hours", FloatDuration::hours(2.0)).unwrap(),
+        assert_eq!(format_duration("%H hours", Duration::from_secs(7200)).unwrap(),
                    "02 hours");
-        assert_eq!(format_duration("%H:%M", FloatDuration::hours(2.5)).unwrap(),
+        assert_eq!(format_duration("%H:%M", Duration::from_secs(9000)).unwrap(),
                    "02:30");
-        assert_eq!(format_duration("%S.%x'%y'%z",
-                                   FloatDuration::seconds(2.5) +
-                                   FloatDuration::microseconds(100.0))
-                           .unwrap(),
+        assert_eq!(format_duration("%S.%x'%y'%z", Duration::from_micros(2_500_100)).unwrap(),
                    "02.500'100'000");
     }
+
+    #[test]
+    fn test_format_spec() {
+        const CLOCK: FormatSpec<'static> = FormatSpec::parse_const("%H:%M:%S");
+        assert_eq!(CLOCK.with_time(Duration::from_secs(5400))
+                       .unwrap()
+                       .to_string(),
+                   "01:30:00");
+
+        assert_eq!(FormatSpec::new("%H%").unwrap_err(),
+                   FormatError::UnexpectedFieldDelimiter);
+        assert_eq!(FormatSpec::new("%Q").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(FormatSpec::new("%é").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(format_duration("%s%%", Duration::from_secs(5)).unwrap(), "5%");
+    }
+
+    #[test]
+    fn test_format_lossy() {
+        let time = Duration::from_secs(5400);
+        assert_eq!(format_lossy("%H:%M", time), "01:30");
+        assert_eq!(format_lossy("%H:%Q %é 100%", time), "01:%Q %é 100%");
+        assert_eq!(format_lossy("%(T%0) %(T%12)", time), "%(T%0) 1");
+    }
+
+    // A `FloatDuration` that doesn't decompose is reported, or written as its raw seconds.
+    #[cfg(feature = "float_duration")]
+    #[test]
+    fn test_float_duration() {
+        assert_eq!(format_duration("%s", FloatDuration::seconds(f64::NAN)).unwrap_err(),
+                   FormatError::DecomposeError(decomposed::DecomposeError::NotFinite));
+        assert_eq!(format_lossy("%H:%M", FloatDuration::seconds(f64::NAN)), "NaN");
+        assert_eq!(format_lossy("%H:%M", FloatDuration::years(1.0e20)),
+                   (FloatDuration::years(1.0e20).as_seconds()).to_string());
+
+        let nan = Formatted::new("%H:%M", FloatDuration::seconds(f64::NAN)).unwrap();
+        assert_eq!(nan.to_string(), "NaN");
+    }
+
+    #[test]
+    fn test_leading_zeros() {
+        let spec = FormatSpec::new("%H:%M:%S").unwrap();
+        let time = Duration::from_secs(7205);
+        let format = |leading_zeros| {
+            spec.with_leading_zeros(leading_zeros)
+                .with_time(time)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(format(LeadingZeros::All), "02:00:05");
+        assert_eq!(format(LeadingZeros::ExceptLargest), "2:00:05");
+        assert_eq!(format(LeadingZeros::None), "2:0:5");
+
+        let spec = FormatSpec::new("%D days %M:%S").unwrap()
+            .with_leading_zeros(LeadingZeros::ExceptLargest);
+        assert_eq!(spec.with_time(Duration::from_secs(65)).unwrap().to_string(),
+                   "0 days 1:05");
+    }
+
+    #[test]
+    fn test_cycle_fields() {
+        let time = Duration::from_secs(39 * 3600 + 5 * 60);
+        assert_eq!(format_duration("%(T%12):%M", time).unwrap(), "3:05");
+        assert_eq!(format_duration("%(T%8)h into shift, %(H%12)", time).unwrap(),
+                   "7h into shift, 03");
+        assert_eq!(format_duration("%(m%60)%%", time).unwrap(), "5%");
+
+        assert_eq!(FormatSpec::new("%(T%0)").unwrap_err(), FormatError::ValueOutOfRange);
+        assert_eq!(FormatSpec::new("%(f%2)").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(FormatSpec::new("%(T%)").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(FormatSpec::new("%(T%12").unwrap_err(),
+                   FormatError::UnexpectedFieldDelimiter);
+        assert_eq!(FormatSpec::new("%(").unwrap_err(),
+                   FormatError::UnexpectedFieldDelimiter);
+    }
+
+    // `%T` and `%U` hold the largest time a `DecomposedTime` can store.
+    #[test]
+    fn test_total_fields_at_max() {
+        let time = DecomposedTime::new(u64::MAX, u32::MAX, u32::MAX, 59, 59, 0.0, true);
+        let days = u64::MAX as u128 * 365 + u32::MAX as u128;
+        let hours = days * 24 + u32::MAX as u128;
+        assert_eq!(format_duration("%U %T", time.clone()).unwrap(),
+                   format!("{} {}", days, hours));
+        assert_eq!(format_duration("%(T%24)", time).unwrap(), (hours % 24).to_string());
+    }
+
+    #[test]
+    fn test_padded_fields() {
+        let time = Duration::from_secs(7 * 86_400 + 3 * 3600);
+        assert_eq!(format_duration("%2Yy %3Dd %H:%M", time).unwrap(), "00y 007d 03:00");
+        assert_eq!(format_duration("%1D", Duration::from_secs(42 * 86_400)).unwrap(), "42");
+        assert_eq!(format_lossy("%3H %2D", time), "%3H 07");
+
+        assert_eq!(FormatSpec::new("%3H").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(FormatSpec::new("%0D").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(FormatSpec::new("%2").unwrap_err(), FormatError::UnexpectedFieldDelimiter);
+    }
+
+    #[test]
+    fn test_preset_fields() {
+        let time = Duration::from_millis(7_205_500);
+        assert_eq!(format_duration("Job ran for %{compact} (%T hours)", time).unwrap(),
+                   "Job ran for 2h5s500ms (2 hours)");
+        assert_eq!(format_duration("[%{clock}] %{si}", Duration::from_millis(1500)).unwrap(),
+                   "[00:01.500] 1.5s");
+        assert_eq!(format_duration("%{decimal}", Duration::from_secs(12 * 3600)).unwrap(),
+                   "5:00:00");
+        assert_eq!(format_lossy("%{compact} %{bogus}", Duration::from_secs(180)),
+                   "3m %{bogus}");
+
+        assert_eq!(FormatSpec::new("%{bogus}").unwrap_err(), FormatError::UnknownField);
+        assert_eq!(FormatSpec::new("%{clock").unwrap_err(),
+                   FormatError::UnexpectedFieldDelimiter);
+    }
+
+    #[test]
+    fn test_geological_fields() {
+        let age = DecomposedTime::from_total_seconds(4_500_000_000 * 365 * 86_400, true).unwrap();
+        assert_eq!(format_duration("%G Gyr", age.clone()).unwrap(), "4.5 Gyr");
+        assert_eq!(format_duration("%K kyr", age).unwrap(), "4500000 kyr");
+        let kiloyears = DecomposedTime::zero().with_years(12_500);
+        assert_eq!(format_duration("%K kyr", kiloyears).unwrap(), "12.5 kyr");
+    }
+
+    #[test]
+    fn test_beats() {
+        let spec = FormatSpec::new("@%@").unwrap();
+        assert_eq!(spec.with_time(Duration::from_secs(6 * 3600)).unwrap().to_string(),
+                   "@250.00");
+        assert_eq!(spec.with_beat_decimals(0)
+                       .with_time(Duration::from_secs(100))
+                       .unwrap()
+                       .to_string(),
+                   "@1");
+        assert_eq!(spec.with_beat_decimals(3)
+                       .with_time(Duration::from_micros(43_200))
+                       .unwrap()
+                       .to_string(),
+                   "@0.000");
+        assert_eq!(spec.with_beat_decimals(4)
+                       .with_time(Duration::from_millis(43_200))
+                       .unwrap()
+                       .to_string(),
+                   "@0.5000");
+    }
+
+    #[test]
+    fn test_field_overflow() {
+        let spec = FormatSpec::new("%H:%M:%S.%x").unwrap();
+        let time = DecomposedTime::new(0, 0, 1, 125, 7, 0.0, true);
+        assert_eq!(spec.with_time(time.clone()).unwrap().to_string(), "01:125:07.000");
+        assert_eq!(spec.with_field_overflow(FieldOverflow::Clamp)
+                       .with_time(time.clone())
+                       .unwrap()
+                       .to_string(),
+                   "01:99:07.000");
+
+        assert_eq!(spec.with_field_overflow(FieldOverflow::Error).with_time(time).unwrap_err(),
+                   FormatError::ValueOutOfRange);
+
+        let days = FormatSpec::new("%2D").unwrap().with_field_overflow(FieldOverflow::Clamp);
+        assert_eq!(days.with_time(Duration::from_secs(120 * 86_400)).unwrap().to_string(),
+                   "99");
+    }
+
+    #[test]
+    fn test_formatted() {
+        #[derive(Debug)]
+        struct Job {
+            elapsed: Formatted<'static, Duration>,
+        }
+
+        let job = Job { elapsed: Formatted::new("%M:%S", Duration::from_secs(65)).unwrap() };
+        assert_eq!(job.elapsed.to_string(), "01:05");
+        assert_eq!(format!("{:?}", job), r#"Job { elapsed: "01:05" }"#);
+
+        let strict = FormatSpec::new("%S").unwrap().with_strict(true);
+        let broken = Formatted(strict, DecomposedTime::new(0, 0, 0, 0, 75, 0.0, true));
+        assert_eq!(format!("{:?}", broken), r#"Formatted("%S", ValueOutOfRange)"#);
+        assert_eq!(broken.to_string(), "75");
+        let error = FormatSpec::new("%S").unwrap().with_field_overflow(FieldOverflow::Error);
+        let wide = DecomposedTime::new(0, 0, 0, 0, 125, 0.0, true);
+        assert_eq!(Formatted(error, wide).to_string(), "99");
+        assert!(Formatted::new("%Q", Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_pretty() {
+        assert_eq!(pretty(Duration::from_millis(90_250)).to_string(), "01:30.250");
+        assert_eq!(format!("{:?}", Pretty(Duration::from_secs(86_400 + 5))),
+                   "1d 00:00:05");
+        assert_eq!(format!("{:?}", vec![pretty(Duration::from_secs(3))]), "[00:03]");
+    }
+
+    #[test]
+    fn test_cached_format() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<DurationFormat>();
+
+        let format = make_format("%M:%S", Duration::from_secs(65)).unwrap();
+        let mut cached = CachedFormat::new(format);
+        assert!(!cached.is_cached());
+        assert_eq!(cached.to_string(), "01:05");
+        assert!(cached.is_cached());
+
+        cached.set_time(Duration::from_secs(65)).unwrap();
+        assert!(cached.is_cached());
+        cached.set_time(Duration::from_secs(66)).unwrap();
+        assert!(!cached.is_cached());
+        assert_eq!(cached.to_string(), "01:06");
+        assert_eq!(*cached.format(), make_format("%M:%S", Duration::from_secs(66)).unwrap());
+
+        let mut uncached = make_format("%M:%S", Duration::from_secs(1)).unwrap();
+        uncached.set_time(Duration::from_secs(2)).unwrap();
+        assert_eq!(uncached.to_string(), "00:02");
+    }
+
+    #[test]
+    fn test_format_duration_args() {
+        let time = Duration::from_secs(3725);
+        assert_eq!(format_duration_args("Task %{task} took %H:%M:%S", time, &[("task", &"build")])
+                       .unwrap(),
+                   "Task build took 01:02:05");
+        assert_eq!(format_duration_args("%{n}/%{total}: %{compact} (100%%)",
+                                        time,
+                                        &[("n", &3), ("total", &10)])
+                       .unwrap(),
+                   "3/10: 1h2m5s (100%)");
+        assert_eq!(format_duration_args("%{pct} of %M", time, &[("pct", &"50%")]).unwrap(),
+                   "50% of 02");
+        assert_eq!(format_duration_args("%{task} %%{task}", time, &[("task", &"a")]).unwrap(),
+                   "a %{task}");
+        assert_eq!(format_duration_args("%{missing} %H", time, &[("task", &"a")]),
+                   Err(FormatError::UnknownField));
+        assert_eq!(format_duration_args("%{task", time, &[]),
+                   Err(FormatError::UnexpectedFieldDelimiter));
+    }
+
+    #[test]
+    fn test_zero_style() {
+        let spec = FormatSpec::new("%M:%S.%x")
+            .unwrap()
+            .with_zero_style(ZeroStyle::Zero("—"));
+        assert_eq!(spec.with_time(Duration::from_secs(0)).unwrap().to_string(), "—");
+        assert_eq!(spec.with_time(Duration::from_millis(5)).unwrap().to_string(), "00:00.005");
+
+        let spec = spec.with_zero_style(ZeroStyle::Below(Duration::from_millis(1), "<1ms"));
+        assert_eq!(spec.with_time(Duration::from_micros(999)).unwrap().to_string(), "<1ms");
+        let negative = DecomposedTime::from_secs_nanos(0, 20_000, false);
+        assert_eq!(spec.with_time(negative).unwrap().to_string(), "<1ms");
+        assert_eq!(spec.with_time(Duration::from_millis(1)).unwrap().to_string(), "00:00.001");
+    }
+
+    #[test]
+    fn test_missing_large_units() {
+        let spec = FormatSpec::new("%H:%M:%S").unwrap();
+        let time = Duration::from_secs(368 * 86_400 + 5);
+        assert_eq!(spec.with_time(time).unwrap().to_string(), "00:00:05");
+
+        let spec = spec.with_missing_large_units(MissingLargeUnits::Prefix);
+        assert_eq!(spec.with_time(time).unwrap().to_string(), "1yr 3d 00:00:05");
+        assert_eq!(spec.with_time(Duration::from_secs(5)).unwrap().to_string(), "00:00:05");
+
+        let days = FormatSpec::new("%3D days, %H:%M")
+            .unwrap()
+            .with_missing_large_units(MissingLargeUnits::Prefix);
+        assert_eq!(days.with_time(time).unwrap().to_string(), "1yr 003 days, 00:00");
+        let total = FormatSpec::new("%T hours (%(T%24))")
+            .unwrap()
+            .with_missing_large_units(MissingLargeUnits::Prefix);
+        assert_eq!(total.with_time(Duration::from_secs(2 * 86_400)).unwrap().to_string(),
+                   "48 hours (0)");
+    }
+
+    #[test]
+    fn test_number_formatter() {
+        struct Superscript;
+        impl NumberFormatter for Superscript {
+            fn write_number(&self,
+                            f: &mut dyn fmt::Write,
+                            field: char,
+                            text: &str)
+                            -> fmt::Result {
+                if field != 'x' {
+                    return f.write_str(text);
+                }
+                let digits: Vec<char> = "⁰¹²³⁴⁵⁶⁷⁸⁹".chars().collect();
+                for ch in text.chars() {
+                    f.write_char(ch.to_digit(10).map_or(ch, |digit| digits[digit as usize]))?;
+                }
+                Ok(())
+            }
+        }
+
+        let format = make_format("%M:%S.%x", Duration::from_millis(65_250)).unwrap();
+        assert_eq!(format.with_numbers(&Superscript).to_string(), "01:05.²⁵⁰");
+        assert_eq!(format.with_numbers(&Numerals::Thai).to_string(), "๐๑:๐๕.๒๕๐");
+    }
+
+    #[test]
+    fn test_fraction() {
+        let time = DecomposedTime::zero().with_fractional_seconds(0.1 + 0.2);
+        assert_eq!(format_duration("%f", time.clone()).unwrap(), "0.3");
+        assert_eq!(format_duration("%F", time.clone()).unwrap(), "0.30000");
+        assert_eq!(format_duration("%1f|%9f", time).unwrap(), "0.3|0.300000000");
+        assert_eq!(format_duration("%f", Duration::new(4, 0)).unwrap(), "0");
+        assert_eq!(format_duration("%F", Duration::new(4, 999_999_999)).unwrap(), "0.99999");
+        assert_eq!(format_duration("%S%f", Duration::new(4, 1)).unwrap(), "040.000000001");
+        let overflowing = DecomposedTime::new(0, 0, 0, 0, 0, 1.5, true);
+        assert_eq!(format_duration("%f", overflowing).unwrap(), "1.5");
+    }
+
+    #[test]
+    fn test_max_precision() {
+        let spec = FormatSpec::new("%M:%S.%x'%y'%z (%f)").unwrap();
+        let time = Duration::new(65, 1_500_000);
+        assert_eq!(spec.with_time(time).unwrap().to_string(),
+                   "01:05.001'500'000 (0.0015)");
+        assert_eq!(spec.with_max_precision(Unit::Milliseconds)
+                       .with_time(time)
+                       .unwrap()
+                       .to_string(),
+                   "01:05.001'000'000 (0.001)");
+        assert_eq!(spec.with_max_precision(Unit::Minutes)
+                       .with_time(time)
+                       .unwrap()
+                       .to_string(),
+                   "01:00.000'000'000 (0)");
+    }
+
+    #[test]
+    fn test_strict() {
+        let spec = FormatSpec::new("%M:%S").unwrap();
+        let bad = DecomposedTime::new(0, 0, 0, 1, 75, 0.0, true);
+        assert_eq!(spec.with_time(bad.clone()).unwrap().to_string(), "01:75");
+        assert_eq!(spec.with_strict(true).with_time(bad).unwrap_err(),
+                   FormatError::ValueOutOfRange);
+        assert_eq!(spec.with_strict(true)
+                       .with_time(Duration::from_secs(135))
+                       .unwrap()
+                       .to_string(),
+                   "02:15");
+    }
+
+    #[test]
+    fn test_numerals() {
+        let spec = FormatSpec::new("%H:%M:%S.%x h").unwrap();
+        let time = Duration::new(3723, 450_000_000);
+        assert_eq!(spec.with_numerals(Numerals::ArabicIndic)
+                       .with_time(time)
+                       .unwrap()
+                       .to_string(),
+                   "٠١:٠٢:٠٣.٤٥٠ h");
+        assert_eq!(spec.with_numerals(Numerals::Devanagari)
+                       .with_time(time)
+                       .unwrap()
+                       .to_string(),
+                   "०१:०२:०३.४५० h");
+        let custom = Numerals::Custom(['o', 'i', '2', '3', '4', '5', '6', '7', '8', '9']);
+        assert_eq!(FormatSpec::new("%s 10")
+                       .unwrap()
+                       .with_numerals(custom)
+                       .with_time(Duration::from_secs(10))
+                       .unwrap()
+                       .to_string(),
+                   "io 10");
+    }
+
+    #[test]
+    fn test_bidi_isolates() {
+        let spec = FormatSpec::new("مدة %H:%M:%S.%x، %D يوم %s%%").unwrap()
+            .with_bidi_isolates(true);
+        assert_eq!(spec.with_time(Duration::new(90_061, 5_000_000)).unwrap().to_string(),
+                   concat!("مدة \u{2066}01:01:01.005\u{2069}، ",
+                           "\u{2066}1\u{2069} يوم \u{2066}1%\u{2069}"));
+        assert_eq!(FormatSpec::new("%%").unwrap()
+                       .with_bidi_isolates(true)
+                       .with_time(Duration::new(0, 0))
+                       .unwrap()
+                       .to_string(),
+                   "%");
+    }
+
+    #[test]
+    fn test_format_small() {
+        let s = format_duration_small("%H:%M", Duration::from_secs(9000)).unwrap();
+        assert!(s.is_inline());
+        assert_eq!(s, "02:30");
+    }
+
+    #[test]
+    fn test_large_totals() {
+        let time = DecomposedTime::default().with_years(u64::MAX).with_hours(5);
+        assert_eq!(format_duration("%U", time.clone()).unwrap(),
+                   format!("{}", u64::MAX as u128 * 365));
+        assert_eq!(format_duration("%T", time).unwrap(),
+                   format!("{}", u64::MAX as u128 * 365 * 24 + 5));
+    }
 }
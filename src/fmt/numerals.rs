@@ -0,0 +1,58 @@
+use std::fmt;
+
+// Digit shapes used for field values. `Custom` maps '0'..='9' to the given characters, for
+// numeral systems not listed here.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Numerals {
+    #[default]
+    Latin,
+    ArabicIndic,
+    ExtendedArabicIndic,
+    Devanagari,
+    Bengali,
+    Thai,
+    Custom([char; 10]),
+}
+
+impl Numerals {
+    pub fn digit(self, value: u8) -> char {
+        assert!(value < 10, "not a decimal digit");
+        let zero = match self {
+            Numerals::Latin => '0',
+            Numerals::ArabicIndic => '\u{660}',
+            Numerals::ExtendedArabicIndic => '\u{6f0}',
+            Numerals::Devanagari => '\u{966}',
+            Numerals::Bengali => '\u{9e6}',
+            Numerals::Thai => '\u{e50}',
+            Numerals::Custom(digits) => return digits[value as usize],
+        };
+        ::std::char::from_u32(zero as u32 + value as u32).expect("numeral blocks are contiguous")
+    }
+}
+
+// Writes the rendered text of a numeric field, called once per field with the field letter
+// ('D', 'x', ... or '{' for a preset). Implement it for digit styles beyond `Numerals`:
+// superscripts, fullwidth or monospace figures, markup around tabular numbers.
+pub trait NumberFormatter {
+    fn write_number(&self, f: &mut dyn fmt::Write, field: char, text: &str) -> fmt::Result;
+
+    // Literal text from the format string, one run between two fields at a time.
+    fn write_literal(&self, f: &mut dyn fmt::Write, text: &str) -> fmt::Result {
+        f.write_str(text)
+    }
+}
+
+impl NumberFormatter for Numerals {
+    fn write_number(&self, f: &mut dyn fmt::Write, _: char, text: &str) -> fmt::Result {
+        if *self == Numerals::Latin {
+            return f.write_str(text);
+        }
+        for ch in text.chars() {
+            match ch {
+                '0'..='9' => f.write_char(self.digit(ch as u8 - b'0'))?,
+                _ => f.write_char(ch)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt::Write;
+
+use rayon::prelude::*;
+
+use decomposed::Decompose;
+use super::{FormatError, FormatSpec};
+
+const CHUNK_SIZE: usize = 4096;
+
+pub fn par_format_durations<D>(spec: FormatSpec,
+                               durations: &[D])
+                               -> Result<Vec<String>, FormatError>
+    where D: Decompose + Clone + Sync
+{
+    let chunks = durations.par_chunks(CHUNK_SIZE)
+        .map(|chunk| format_chunk(spec, chunk))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut formatted = Vec::with_capacity(durations.len());
+    for chunk in chunks {
+        formatted.extend(chunk);
+    }
+    Ok(formatted)
+}
+
+fn format_chunk<D>(spec: FormatSpec, chunk: &[D]) -> Result<Vec<String>, FormatError>
+    where D: Decompose + Clone
+{
+    let mut formatted = Vec::with_capacity(chunk.len());
+    for time in chunk {
+        let mut s = String::new();
+        write!(s, "{}", spec.with_time(time.clone())?)?;
+        formatted.push(s);
+    }
+    Ok(formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_preserves_order() {
+        let spec = FormatSpec::new("%s").unwrap();
+        let durations = (0..10_000)
+            .map(|i| Duration::from_secs(i % 60))
+            .collect::<Vec<_>>();
+        let formatted = par_format_durations(spec, &durations).unwrap();
+
+        assert_eq!(formatted.len(), durations.len());
+        for (i, s) in formatted.iter().enumerate() {
+            assert_eq!(*s, (i % 60).to_string());
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use super::{has_field, parse_cycle_field, FieldOverflow, FormatSpec, LeadingZeros,
+            MissingLargeUnits, Numerals, ZeroStyle, FIELD_DELIMITER, LEFT_TO_RIGHT_ISOLATE,
+            POP_DIRECTIONAL_ISOLATE};
+
+impl<'a> FormatSpec<'a> {
+    // An anchored regular expression matching whatever this spec renders, so front-ends and API
+    // schemas can check a duration string before it reaches a backend using the same spec. It
+    // sticks to syntax ECMAScript, PCRE and the `regex` crate share, making it usable as a JSON
+    // Schema `pattern`. Field widths, numerals, isolates and the zero style are followed exactly;
+    // presets are matched by shape only.
+    pub fn to_regex(&self) -> String {
+        let digit = digit_class(self.numerals);
+        let mut body = String::new();
+        let mut chars = self.format.chars();
+        let mut largest = true;
+        let mut isolated = false;
+
+        if self.missing_large_units == MissingLargeUnits::Prefix {
+            if !has_field(self.format, &['Y', 'K', 'G', 'T', 'U', '@']) {
+                body += &format!("(?:{}+yr )?", digit);
+            }
+            if !has_field(self.format, &['D', 'T', 'U', '@']) {
+                body += &format!("(?:{}+d )?", digit);
+            }
+        }
+
+        while let Some(ch) = chars.next() {
+            if ch != FIELD_DELIMITER {
+                if isolated && !ch.is_ascii_punctuation() {
+                    body.push(POP_DIRECTIONAL_ISOLATE);
+                    isolated = false;
+                }
+                push_literal(&mut body, ch);
+                continue;
+            }
+            let field = match chars.next() {
+                Some(FIELD_DELIMITER) => {
+                    push_literal(&mut body, FIELD_DELIMITER);
+                    continue;
+                }
+                Some(field) => field,
+                None => break,
+            };
+            if self.bidi_isolates && !isolated {
+                body.push(LEFT_TO_RIGHT_ISOLATE);
+                isolated = true;
+            }
+
+            let field = match field {
+                '(' => {
+                    let (field, _) = parse_cycle_field(&mut chars)
+                        .expect("format was validated by FormatSpec::new");
+                    let width = match field {
+                        'x' | 'y' | 'z' => 3,
+                        'S' | 'M' | 'H' => self.leading_zeros.width(largest),
+                        _ => 0,
+                    };
+                    body += &repeat(&digit, width, true);
+                    field
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').unwrap_or(rest.len());
+                    let leading_zeros = self.leading_zeros.for_clock(largest);
+                    body += &preset_pattern(&rest[..end], &digit, leading_zeros);
+                    chars = rest[(end + 1).min(rest.len())..].chars();
+                    field
+                }
+                '1'..='9' => {
+                    let width = field as usize - '0' as usize;
+                    let field = chars.next().unwrap_or('D');
+                    body += &match field {
+                        'f' => format!("{}+\\.{}{{{}}}", digit, digit, width),
+                        _ => self.fixed(&digit, width),
+                    };
+                    field
+                }
+                field => {
+                    body += &self.field_pattern(field, largest, &digit);
+                    field
+                }
+            };
+            largest &= !matches!(field, 'H' | 'M' | 'S');
+        }
+        if isolated {
+            body.push(POP_DIRECTIONAL_ISOLATE);
+        }
+
+        match self.zero_style {
+            ZeroStyle::Normal => format!("^{}$", body),
+            ZeroStyle::Zero(text) |
+            ZeroStyle::Below(_, text) => {
+                let mut zero = String::new();
+                text.chars().for_each(|ch| push_literal(&mut zero, ch));
+                format!("^(?:{}|{})$", zero, body)
+            }
+        }
+    }
+
+    // `{"type":"string","pattern":"..."}` with `to_regex` as the pattern.
+    pub fn to_json_schema(&self) -> String {
+        let mut schema = String::from(r#"{"type":"string","pattern":""#);
+        for ch in self.to_regex().chars() {
+            match ch {
+                '"' => schema.push_str("\\\""),
+                '\\' => schema.push_str("\\\\"),
+                ch if (ch as u32) < 0x20 => schema += &format!("\\u{:04x}", ch as u32),
+                ch => schema.push(ch),
+            }
+        }
+        schema.push_str("\"}");
+        schema
+    }
+
+    fn field_pattern(&self, field: char, largest: bool, digit: &str) -> String {
+        match field {
+            'x' | 'y' | 'z' => self.fixed(digit, 3),
+            'S' | 'M' | 'H' => self.fixed(digit, self.leading_zeros.width(largest)),
+            'f' | 'K' | 'G' => format!("{}+(?:\\.{}+)?", digit, digit),
+            'F' => format!("{}+\\.{}{{5}}", digit, digit),
+            '@' if self.beat_decimals > 0 => {
+                format!("{}+\\.{}{{{}}}", digit, digit, self.beat_decimals)
+            }
+            _ => format!("{}+", digit),
+        }
+    }
+
+    // A field zero-padded to `width`, which `FieldOverflow::Widen` lets grow past it.
+    fn fixed(&self, digit: &str, width: usize) -> String {
+        repeat(digit, width, self.field_overflow == FieldOverflow::Widen)
+    }
+}
+
+fn repeat(digit: &str, width: usize, wider: bool) -> String {
+    match width {
+        0 => format!("{}+", digit),
+        width if wider => format!("{}{{{},}}", digit, width),
+        width => format!("{}{{{}}}", digit, width),
+    }
+}
+
+// The clock's hours and minutes are only padded when `leading_zeros` pads the largest unit.
+fn preset_pattern(preset: &str, digit: &str, leading_zeros: LeadingZeros) -> String {
+    let d = digit;
+    match preset {
+        "clock" => {
+            format!(concat!("-?(?:{d}+yr )?(?:{d}+d )?(?:{large}:)?{large}:{small}",
+                            "(?:\\.{d}{{3}}(?:'{d}{{3}}){{0,2}})?"),
+                    d = d,
+                    large = repeat(d, leading_zeros.width(true), true),
+                    small = repeat(d, leading_zeros.width(false), true))
+        }
+        "compact" => format!("-?(?:{}+(?:h|m|s|ms|us|ns))+", d),
+        "decimal" => format!("-?(?:{d}+d )?{d}:{d}{{2}}:{d}{{2}}", d = d),
+        _ => format!("-?{d}+(?:\\.{d}+)?(?:s|ms|µs|ns)", d = d),
+    }
+}
+
+fn digit_class(numerals: Numerals) -> String {
+    if numerals == Numerals::Latin {
+        return "[0-9]".to_owned();
+    }
+    let mut class = String::from("[");
+    for value in 0..10 {
+        let digit = numerals.digit(value);
+        if matches!(digit, '\\' | ']' | '[' | '^' | '-') {
+            class.push('\\');
+        }
+        class.push(digit);
+    }
+    class.push(']');
+    class
+}
+
+// Only ECMAScript's syntax characters are escaped: in its Unicode mode escaping anything else is
+// an error.
+fn push_literal(regex: &mut String, ch: char) {
+    if "^$\\.*+?()[]{}|/".contains(ch) {
+        regex.push('\\');
+    }
+    regex.push(ch);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fmt::LeadingZeros;
+
+    #[test]
+    fn test_to_regex() {
+        let spec = |format| FormatSpec::new(format).unwrap();
+        assert_eq!(spec("%H:%M:%S.%x").to_regex(),
+                   "^[0-9]{2,}:[0-9]{2,}:[0-9]{2,}\\.[0-9]{3,}$");
+        assert_eq!(spec("%H:%M (%2D days) %% %3f")
+                       .with_leading_zeros(LeadingZeros::ExceptLargest)
+                       .with_field_overflow(FieldOverflow::Clamp)
+                       .to_regex(),
+                   "^[0-9]+:[0-9]{2} \\([0-9]{2} days\\) % [0-9]+\\.[0-9]{3}$");
+        assert_eq!(spec("%(T%12)h %@").to_regex(), "^[0-9]+h [0-9]+\\.[0-9]{2}$");
+        assert_eq!(spec("%{compact}").to_regex(), "^-?(?:[0-9]+(?:h|m|s|ms|us|ns))+$");
+        assert_eq!(spec("%M:%S")
+                       .with_zero_style(ZeroStyle::Zero("-"))
+                       .with_missing_large_units(MissingLargeUnits::Prefix)
+                       .to_regex(),
+                   "^(?:-|(?:[0-9]+yr )?(?:[0-9]+d )?[0-9]{2,}:[0-9]{2,})$");
+        assert_eq!(spec("%m min")
+                       .with_bidi_isolates(true)
+                       .with_numerals(Numerals::Thai)
+                       .to_regex(),
+                   "^\u{2066}[๐๑๒๓๔๕๖๗๘๙]+\u{2069} min$");
+    }
+
+    #[test]
+    fn test_to_regex_matches_output() {
+        use decomposed::{Decompose, DecomposedTime};
+        use regex::Regex;
+        use std::time::Duration;
+
+        let mut times: Vec<DecomposedTime> = [0, 5, 65, 3_605, 90_061, 31_626_061]
+            .iter()
+            .map(|&secs| Duration::from_secs(secs).decompose().unwrap())
+            .collect();
+        times.push(Duration::new(3_723, 4_005_000).decompose().unwrap());
+        times.push(DecomposedTime::new(0, 0, 1, 2, 3, 0.5, false));
+
+        let formats = ["%{clock}", "%H:%M:%S.%x", "%S %{clock}", "%D days %H:%M", "%T:%M %3f",
+                       "%{compact}", "%{decimal}", "%{si}", "%(T%12)h %@"];
+        let leading_zeros = [LeadingZeros::All, LeadingZeros::ExceptLargest, LeadingZeros::None];
+        for format in &formats {
+            for &leading_zeros in &leading_zeros {
+                let spec = FormatSpec::new(format).unwrap().with_leading_zeros(leading_zeros);
+                let regex = Regex::new(&spec.to_regex()).unwrap();
+                for time in &times {
+                    let text = spec.with_time(time.clone()).unwrap().to_string();
+                    assert!(regex.is_match(&text), "{:?} doesn't match {}", text, regex);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_json_schema() {
+        assert_eq!(FormatSpec::new("%s.%x s").unwrap().to_json_schema(),
+                   r#"{"type":"string","pattern":"^[0-9]+\\.[0-9]{3,} s$"}"#);
+    }
+}
@@ -0,0 +1,177 @@
+use decomposed::Decompose;
+use super::{FormatError, UnitSpacing};
+use units::Unit;
+
+// An upper end below this many of the lower end's unit is written in that unit too, so 45 s to
+// 90 s is "45–90 s" while 45 s to 120 s is "45 s–2 min".
+const MAX_SHARED_VALUE: u128 = 100;
+
+// How `format_range` writes its ends: the separator between them (an en dash by default), the
+// spacing between a count and its unit, and the most decimals a count is given. Trailing zero
+// decimals are dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RangeOptions {
+    separator: &'static str,
+    spacing: UnitSpacing,
+    precision: usize,
+}
+
+// Writes both ends in one unit with the label given once, "2–3 h", when they share a unit or
+// the upper end stays small in the lower end's unit; otherwise each end gets its own unit,
+// "45 s–2 min". Signs are dropped and `lo` must not be longer than `hi`.
+pub fn format_range<L, H>(lo: L, hi: H, options: &RangeOptions) -> Result<String, FormatError>
+    where L: Decompose,
+          H: Decompose
+{
+    let lo = lo.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let hi = hi.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let lo = lo.total_nanoseconds().ok_or(FormatError::ValueOutOfRange)?;
+    let hi = hi.total_nanoseconds().ok_or(FormatError::ValueOutOfRange)?;
+    if lo > hi {
+        return Err(FormatError::ValueOutOfRange);
+    }
+
+    let (lo_unit, hi_unit) = (natural_unit(lo), natural_unit(hi));
+    let spacing = options.spacing.as_str();
+    let shared = if lo == 0 || lo_unit == hi_unit {
+        Some(hi_unit)
+    } else if hi / (lo_unit.nanoseconds() as u128) < MAX_SHARED_VALUE {
+        Some(lo_unit)
+    } else {
+        None
+    };
+    Ok(match shared {
+           Some(unit) => {
+               format!("{}{}{}{}{}",
+                       options.count(lo, unit)?,
+                       options.separator,
+                       options.count(hi, unit)?,
+                       spacing,
+                       label(unit))
+           }
+           None => {
+               format!("{}{}{}{}{}{}{}",
+                       options.count(lo, lo_unit)?,
+                       spacing,
+                       label(lo_unit),
+                       options.separator,
+                       options.count(hi, hi_unit)?,
+                       spacing,
+                       label(hi_unit))
+           }
+       })
+}
+
+impl RangeOptions {
+    pub const fn new() -> RangeOptions {
+        RangeOptions {
+            separator: "\u{2013}",
+            spacing: UnitSpacing::Space,
+            precision: 1,
+        }
+    }
+    #[must_use]
+    pub fn with_separator(mut self, separator: &'static str) -> RangeOptions {
+        self.separator = separator;
+        self
+    }
+    #[must_use]
+    pub fn with_spacing(mut self, spacing: UnitSpacing) -> RangeOptions {
+        self.spacing = spacing;
+        self
+    }
+    #[must_use]
+    pub fn with_precision(mut self, precision: usize) -> RangeOptions {
+        assert!(precision <= 9, "precision beyond nanoseconds");
+        self.precision = precision;
+        self
+    }
+
+    pub fn separator(&self) -> &'static str {
+        self.separator
+    }
+    pub fn spacing(&self) -> UnitSpacing {
+        self.spacing
+    }
+    pub fn precision(&self) -> usize {
+        self.precision
+    }
+
+    // `nanos` in `unit`, rounded half up to `precision` decimals. The whole units and the
+    // remainder are scaled apart, so only a count too large for u128 once scaled overflows.
+    fn count(&self, nanos: u128, unit: Unit) -> Result<String, FormatError> {
+        let denominator = 10u128.pow(self.precision as u32);
+        let scale = unit.nanoseconds() as u128;
+        let fraction = ((nanos % scale) * denominator * 2 + scale) / (scale * 2);
+        let rounded = (nanos / scale)
+            .checked_mul(denominator)
+            .and_then(|whole| whole.checked_add(fraction))
+            .ok_or(FormatError::ValueOutOfRange)?;
+        let fraction = format!("{:0width$}", rounded % denominator, width = self.precision);
+        let fraction = fraction.trim_end_matches('0');
+        Ok(if fraction.is_empty() {
+               (rounded / denominator).to_string()
+           } else {
+               format!("{}.{}", rounded / denominator, fraction)
+           })
+    }
+}
+
+impl Default for RangeOptions {
+    fn default() -> RangeOptions {
+        RangeOptions::new()
+    }
+}
+
+// The largest unit `nanos` has at least one of.
+fn natural_unit(nanos: u128) -> Unit {
+    Unit::ALL
+        .iter()
+        .cloned()
+        .find(|&unit| nanos >= unit.nanoseconds() as u128)
+        .unwrap_or(Unit::Nanoseconds)
+}
+
+fn label(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Minutes => "min",
+        _ => unit.symbol(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decomposed::DecomposedTime;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_range() {
+        let options = RangeOptions::new();
+        let range = |lo: u64, hi: u64| {
+            format_range(Duration::from_secs(lo), Duration::from_secs(hi), &options).unwrap()
+        };
+        assert_eq!(range(7200, 10_800), "2\u{2013}3 h");
+        assert_eq!(range(45, 90), "45\u{2013}90 s");
+        assert_eq!(range(45, 120), "45 s\u{2013}2 min");
+        assert_eq!(range(5400, 9000), "1.5\u{2013}2.5 h");
+        assert_eq!(range(0, 600), "0\u{2013}10 min");
+        assert_eq!(range(60, 60), "1\u{2013}1 min");
+
+        let options = RangeOptions::new().with_separator("-").with_spacing(UnitSpacing::None);
+        assert_eq!(format_range(Duration::from_micros(250),
+                                Duration::from_micros(1_500),
+                                &options)
+                           .unwrap(),
+                   "250µs-1.5ms");
+        assert_eq!(format_range(Duration::from_secs(2), Duration::from_secs(1), &options),
+                   Err(FormatError::ValueOutOfRange));
+
+        let longest = DecomposedTime::new(u64::MAX, 0, 0, 0, 0, 0.0, true);
+        assert_eq!(format_range(Duration::from_secs(0),
+                                longest,
+                                &RangeOptions::new().with_precision(9))
+                           .unwrap(),
+                   "0\u{2013}18446744073709551615 y");
+    }
+}
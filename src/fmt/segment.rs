@@ -0,0 +1,60 @@
+use decomposed::Decompose;
+use super::FormatError;
+
+// Output for seven-segment and character LCDs: only digits, ':', '.' and '-', at most `cells`
+// characters (punctuation included). Subsecond decimals are dropped first, then the seconds of
+// an "H:MM:SS" clock, leaving "H:MM"; if even that doesn't fit the result is
+// `ValueOutOfRange`.
+pub fn format_seven_segment<D>(time: D, cells: usize) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let sign = if time.is_negative() && time.total_nanoseconds() != Some(0) { "-" } else { "" };
+    let hours = time.total_hours();
+
+    let clock = if hours > 0 {
+        format!("{}{}:{:02}:{:02}", sign, hours, time.minutes(), time.seconds())
+    } else {
+        format!("{}{}:{:02}", sign, time.minutes(), time.seconds())
+    };
+    let millis = format!("{:03}", time.milliseconds());
+    for decimals in (1..4).rev() {
+        if clock.len() + 1 + decimals <= cells {
+            return Ok(format!("{}.{}", clock, &millis[..decimals]));
+        }
+    }
+    if clock.len() <= cells {
+        return Ok(clock);
+    }
+
+    let short = format!("{}{}:{:02}", sign, hours, time.minutes());
+    if hours > 0 && short.len() <= cells {
+        return Ok(short);
+    }
+    Err(FormatError::ValueOutOfRange)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decomposed::DecomposedTime;
+    use std::time::Duration;
+
+    #[test]
+    fn test_seven_segment() {
+        let time = Duration::from_millis(3_725_250);
+        assert_eq!(format_seven_segment(time, 12).unwrap(), "1:02:05.250");
+        assert_eq!(format_seven_segment(time, 9).unwrap(), "1:02:05.2");
+        assert_eq!(format_seven_segment(time, 7).unwrap(), "1:02:05");
+        assert_eq!(format_seven_segment(time, 4).unwrap(), "1:02");
+        assert_eq!(format_seven_segment(time, 3).unwrap_err(), FormatError::ValueOutOfRange);
+
+        assert_eq!(format_seven_segment(Duration::from_millis(65_400), 6).unwrap(), "1:05.4");
+        assert_eq!(format_seven_segment(Duration::from_secs(5), 4).unwrap(), "0:05");
+        assert_eq!(format_seven_segment(Duration::from_secs(5), 3).unwrap_err(),
+                   FormatError::ValueOutOfRange);
+
+        let negative = DecomposedTime::new(0, 0, 0, 1, 5, 0.0, false);
+        assert_eq!(format_seven_segment(negative, 5).unwrap(), "-1:05");
+    }
+}
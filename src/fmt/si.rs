@@ -0,0 +1,219 @@
+use std::fmt;
+use std::str;
+
+use decomposed::{Decompose, DecomposedTime};
+use super::{FormatError, UnitSpacing};
+
+const DEFAULT_PRECISION: usize = 3;
+const SCALES: [(u128, usize, &str); 4] = [(1_000_000_000, 9, "s"),
+                                          (1_000_000, 6, "ms"),
+                                          (1_000, 3, "µs"),
+                                          (1, 0, "ns")];
+
+// Renders a duration in the largest of s/ms/µs/ns that keeps the value at or above one, with
+// up to three decimals by default. An explicit precision (`{:.1}`) prints exactly that many.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SiDuration {
+    time: DecomposedTime,
+}
+
+// A measurement and its uncertainty, "12.3 ms ± 0.4 ms", both in the unit `SiDuration` would
+// pick for the larger of the two. The error is rounded to one significant digit (two when it
+// starts with a 1) and the value to the same decimal place; an explicit precision sets the
+// decimals of both.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Uncertain {
+    value: DecomposedTime,
+    error: DecomposedTime,
+    spacing: UnitSpacing,
+}
+
+pub fn format_si<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    Ok(SiDuration::new(time).to_string())
+}
+
+impl SiDuration {
+    pub fn new(time: DecomposedTime) -> SiDuration {
+        SiDuration { time }
+    }
+    pub fn time(&self) -> &DecomposedTime {
+        &self.time
+    }
+
+    fn total_nanoseconds(&self) -> u128 {
+        self.time.total_seconds() * 1_000_000_000 + self.time.subsec_nanoseconds() as u128
+    }
+}
+
+pub fn format_uncertain<D, E>(value: D, error: E) -> Result<String, FormatError>
+    where D: Decompose,
+          E: Decompose
+{
+    let value = value.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let error = error.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    Ok(Uncertain::new(value, error).to_string())
+}
+
+impl Uncertain {
+    pub fn new(value: DecomposedTime, error: DecomposedTime) -> Uncertain {
+        Uncertain {
+            value,
+            error,
+            spacing: UnitSpacing::Space,
+        }
+    }
+    #[must_use]
+    pub fn with_spacing(mut self, spacing: UnitSpacing) -> Uncertain {
+        self.spacing = spacing;
+        self
+    }
+    pub fn value(&self) -> &DecomposedTime {
+        &self.value
+    }
+    pub fn error(&self) -> &DecomposedTime {
+        &self.error
+    }
+}
+
+impl From<DecomposedTime> for SiDuration {
+    fn from(time: DecomposedTime) -> SiDuration {
+        SiDuration::new(time)
+    }
+}
+
+impl fmt::Display for SiDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nanos = self.total_nanoseconds();
+        let &(scale, digits, unit) = SCALES.iter()
+            .find(|&&(scale, _, _)| nanos >= scale)
+            .unwrap_or(&SCALES[SCALES.len() - 1]);
+
+        if self.time.is_negative() && nanos > 0 {
+            f.write_str("-")?;
+        }
+        write!(f, "{}", nanos / scale)?;
+
+        let mut fraction = [b'0'; 9];
+        let mut rem = nanos % scale;
+        for digit in fraction[..digits].iter_mut().rev() {
+            *digit = b'0' + (rem % 10) as u8;
+            rem /= 10;
+        }
+
+        let shown = match f.precision() {
+            Some(precision) => precision,
+            None => {
+                let mut shown = digits.min(DEFAULT_PRECISION);
+                while shown > 0 && fraction[shown - 1] == b'0' {
+                    shown -= 1;
+                }
+                shown
+            }
+        };
+        if shown > 0 {
+            f.write_str(".")?;
+            f.write_str(str::from_utf8(&fraction[..shown.min(digits)]).map_err(|_| fmt::Error)?)?;
+            for _ in digits..shown {
+                f.write_str("0")?;
+            }
+        }
+        f.write_str(unit)
+    }
+}
+
+impl fmt::Display for Uncertain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = SiDuration::new(self.value.clone()).total_nanoseconds();
+        let error = SiDuration::new(self.error.clone()).total_nanoseconds();
+        let &(scale, digits, unit) = SCALES.iter()
+            .find(|&&(scale, _, _)| value.max(error) >= scale)
+            .unwrap_or(&SCALES[SCALES.len() - 1]);
+
+        let decimals = match f.precision() {
+            Some(precision) => precision,
+            None if error == 0 => {
+                let fraction = format!("{:0width$}", value % scale, width = digits);
+                fraction[..digits.min(DEFAULT_PRECISION)].trim_end_matches('0').len()
+            }
+            None => {
+                let error_digits = error.to_string();
+                let significant = if error_digits.starts_with('1') { 2 } else { 1 };
+                (digits + significant).saturating_sub(error_digits.len()).min(digits)
+            }
+        };
+        if self.value.is_negative() && value > 0 {
+            f.write_str("-")?;
+        }
+        let spacing = self.spacing.as_str();
+        write_rounded(f, value, digits, decimals)?;
+        write!(f, "{}{} ± ", spacing, unit)?;
+        write_rounded(f, error, digits, decimals)?;
+        write!(f, "{}{}", spacing, unit)
+    }
+}
+
+// `nanos` in a unit of 10^`digits` ns with `decimals` decimals, rounded half up.
+fn write_rounded(f: &mut fmt::Formatter,
+                 nanos: u128,
+                 digits: usize,
+                 decimals: usize)
+                 -> fmt::Result {
+    let kept = decimals.min(digits);
+    let step = 10u128.pow((digits - kept) as u32);
+    let rounded = (nanos + step / 2) / step;
+    let denominator = 10u128.pow(kept as u32);
+    write!(f, "{}", rounded / denominator)?;
+    if decimals > 0 {
+        write!(f, ".{:0width$}", rounded % denominator, width = kept)?;
+        for _ in kept..decimals {
+            f.write_str("0")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_si() {
+        assert_eq!(format_si(Duration::from_micros(3200)).unwrap(), "3.2ms");
+        assert_eq!(format_si(Duration::from_millis(1500)).unwrap(), "1.5s");
+        assert_eq!(format_si(Duration::from_micros(250)).unwrap(), "250µs");
+        assert_eq!(format_si(Duration::from_nanos(12)).unwrap(), "12ns");
+        assert_eq!(format_si(Duration::from_secs(120)).unwrap(), "120s");
+        assert_eq!(format_si(Duration::from_secs(0)).unwrap(), "0ns");
+        assert_eq!(format_si(DecomposedTime::from_secs_nanos(2, 0, false)).unwrap(), "-2s");
+
+        let time = DecomposedTime::from_secs_nanos(0, 3_200_000, true);
+        assert_eq!(format!("{:.2}", SiDuration::new(time.clone())), "3.20ms");
+        assert_eq!(format!("{:.0}", SiDuration::new(time)), "3ms");
+    }
+
+    #[test]
+    fn test_uncertain() {
+        let value = Duration::from_nanos(12_345_678);
+        assert_eq!(format_uncertain(value, Duration::from_micros(412)).unwrap(),
+                   "12.3 ms ± 0.4 ms");
+        assert_eq!(format_uncertain(value, Duration::from_micros(150)).unwrap(),
+                   "12.35 ms ± 0.15 ms");
+        assert_eq!(format_uncertain(value, Duration::from_millis(2)).unwrap(),
+                   "12 ms ± 2 ms");
+        assert_eq!(format_uncertain(Duration::from_micros(500), Duration::from_millis(2))
+                       .unwrap(),
+                   "1 ms ± 2 ms");
+        assert_eq!(format_uncertain(Duration::from_millis(1500), Duration::from_secs(0)).unwrap(),
+                   "1.5 s ± 0.0 s");
+
+        let uncertain = Uncertain::new(value.decompose().unwrap(),
+                                       Duration::from_micros(50).decompose().unwrap())
+            .with_spacing(UnitSpacing::None);
+        assert_eq!(uncertain.to_string(), "12.35ms ± 0.05ms");
+        assert_eq!(format!("{:.4}", uncertain), "12.3457ms ± 0.0500ms");
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+use std::ops::Deref;
+use std::str;
+
+pub const INLINE_CAPACITY: usize = 32;
+
+#[derive(Clone)]
+enum Repr {
+    Inline { buf: [u8; INLINE_CAPACITY], len: usize },
+    Heap(String),
+}
+
+#[derive(Clone)]
+pub struct SmallString {
+    repr: Repr,
+}
+
+impl SmallString {
+    pub fn new() -> SmallString {
+        SmallString {
+            repr: Repr::Inline {
+                buf: [0; INLINE_CAPACITY],
+                len: 0,
+            },
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self.repr {
+            Repr::Inline { ref buf, len } => inline_str(buf, len),
+            Repr::Heap(ref s) => s,
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        match self.repr {
+            Repr::Inline { .. } => true,
+            Repr::Heap(_) => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        match self.repr {
+            Repr::Inline { ref mut len, .. } => *len = 0,
+            Repr::Heap(ref mut s) => s.clear(),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self.repr {
+            Repr::Inline { .. } => self.as_str().to_owned(),
+            Repr::Heap(s) => s,
+        }
+    }
+}
+
+fn inline_str(buf: &[u8; INLINE_CAPACITY], len: usize) -> &str {
+    // Only whole `&str`s are ever copied in, so the prefix is always valid UTF-8.
+    str::from_utf8(&buf[..len]).expect("inline buffer is not valid UTF-8")
+}
+
+impl Default for SmallString {
+    fn default() -> SmallString {
+        SmallString::new()
+    }
+}
+
+impl fmt::Write for SmallString {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let spilled = match self.repr {
+            Repr::Inline { ref mut buf, ref mut len } => {
+                if *len + s.len() <= INLINE_CAPACITY {
+                    buf[*len..*len + s.len()].copy_from_slice(s.as_bytes());
+                    *len += s.len();
+                    return Ok(());
+                }
+                let mut heap = String::with_capacity(*len + s.len());
+                heap.push_str(inline_str(buf, *len));
+                heap
+            }
+            Repr::Heap(ref mut heap) => {
+                heap.push_str(s);
+                return Ok(());
+            }
+        };
+        self.repr = Repr::Heap(spilled);
+        self.write_str(s)
+    }
+}
+
+impl Deref for SmallString {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SmallString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for SmallString {
+    fn eq(&self, other: &SmallString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+impl Eq for SmallString {}
+
+impl<'a> PartialEq<&'a str> for SmallString {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<SmallString> for String {
+    fn from(s: SmallString) -> String {
+        s.into_string()
+    }
+}
+
+impl fmt::Display for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_spill() {
+        let mut s = SmallString::new();
+        s.write_str("02:30").unwrap();
+        assert!(s.is_inline());
+        assert_eq!(s, "02:30");
+
+        s.write_str(" and then some more text to overflow").unwrap();
+        assert!(!s.is_inline());
+        assert_eq!(s, "02:30 and then some more text to overflow");
+        assert_eq!(String::from(s), "02:30 and then some more text to overflow");
+    }
+}
@@ -0,0 +1,669 @@
+#[cfg(feature = "serde")]
+use std::borrow::Cow;
+#[cfg(feature = "serde")]
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::de::{self, EnumAccess, MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{FormatError, FormatSpec, ZeroStyle};
+#[cfg(feature = "serde")]
+use units::Unit;
+#[cfg(feature = "serde")]
+use super::{FieldOverflow, LeadingZeros, MissingLargeUnits, Numerals};
+
+// A `FormatSpec` that owns its format and zero-style text, for specs built at runtime or read
+// from a file or socket, where there is no input left to borrow them from. `spec` lends it out
+// as a `FormatSpec`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatSpecBuf {
+    // Everything but the strings, which are left empty.
+    options: FormatSpec<'static>,
+    format: String,
+    zero_text: String,
+}
+
+impl FormatSpecBuf {
+    pub fn new(format: &str) -> Result<FormatSpecBuf, FormatError> {
+        FormatSpec::new(format).map(FormatSpecBuf::from)
+    }
+
+    pub fn spec(&self) -> FormatSpec<'_> {
+        FormatSpec {
+            format: &self.format,
+            zero_style: with_zero_text(self.options.zero_style, &self.zero_text),
+            ..self.options
+        }
+    }
+    pub fn as_str(&self) -> &str {
+        &self.format
+    }
+}
+
+impl<'a> From<FormatSpec<'a>> for FormatSpecBuf {
+    fn from(spec: FormatSpec<'a>) -> FormatSpecBuf {
+        let zero_text = match spec.zero_style {
+            ZeroStyle::Normal => "",
+            ZeroStyle::Zero(text) |
+            ZeroStyle::Below(_, text) => text,
+        };
+        FormatSpecBuf {
+            options: FormatSpec {
+                format: "",
+                zero_style: with_zero_text(spec.zero_style, ""),
+                ..spec
+            },
+            format: spec.format.to_owned(),
+            zero_text: zero_text.to_owned(),
+        }
+    }
+}
+
+fn with_zero_text<'a>(zero_style: ZeroStyle<'_>, text: &'a str) -> ZeroStyle<'a> {
+    match zero_style {
+        ZeroStyle::Normal => ZeroStyle::Normal,
+        ZeroStyle::Zero(_) => ZeroStyle::Zero(text),
+        ZeroStyle::Below(threshold, _) => ZeroStyle::Below(threshold, text),
+    }
+}
+
+// A spec is stored as a struct of its options, with the enums as names. `numerals` is a unit
+// variant named after the numeral system, or `custom` holding the ten digits. The zero style is
+// `None` for `Normal`, otherwise its text and `Below`'s threshold as (seconds, nanoseconds).
+// `FormatSpec` borrows its strings from the input, so reading one fails where the input has to
+// be copied, such as from an `io::Read` or an escaped JSON string; `FormatSpecBuf` reads from
+// anything. The format is checked on load, which is a single pass over it, since rendering
+// relies on it being valid.
+#[cfg(feature = "serde")]
+const FIELDS: &[&str] = &["format", "leading_zeros", "max_precision", "strict", "numerals",
+                          "bidi_isolates", "beat_decimals", "field_overflow",
+                          "missing_large_units", "zero_style"];
+
+#[cfg(feature = "serde")]
+impl<'a> Serialize for FormatSpec<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        let mut state = serializer.serialize_struct("FormatSpec", FIELDS.len())?;
+        state.serialize_field("format", self.format)?;
+        state.serialize_field("leading_zeros", leading_zeros_name(self.leading_zeros))?;
+        state.serialize_field("max_precision", self.max_precision.name())?;
+        state.serialize_field("strict", &self.strict)?;
+        state.serialize_field("numerals", &NumeralsName(self.numerals))?;
+        state.serialize_field("bidi_isolates", &self.bidi_isolates)?;
+        state.serialize_field("beat_decimals", &self.beat_decimals)?;
+        state.serialize_field("field_overflow", field_overflow_name(self.field_overflow))?;
+        state.serialize_field("missing_large_units",
+                              missing_large_units_name(self.missing_large_units))?;
+        state.serialize_field("zero_style", &zero_style_parts(self.zero_style))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de: 'a, 'a> Deserialize<'de> for FormatSpec<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<FormatSpec<'a>, D::Error>
+        where D: Deserializer<'de>
+    {
+        let stored = deserializer.deserialize_struct("FormatSpec", FIELDS, StoredVisitor)?;
+        let borrowed = |text: Cow<'de, str>| match text {
+            Cow::Borrowed(text) => Ok(text),
+            Cow::Owned(_) => {
+                Err(de::Error::custom("string can't be borrowed from the input, read a \
+                                       FormatSpecBuf instead"))
+            }
+        };
+        let zero_text = borrowed(stored.zero_text)?;
+        Ok(FormatSpec {
+               format: borrowed(stored.format)?,
+               zero_style: with_zero_text(stored.options.zero_style, zero_text),
+               ..stored.options
+           })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FormatSpecBuf {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        self.spec().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for FormatSpecBuf {
+    fn deserialize<D>(deserializer: D) -> Result<FormatSpecBuf, D::Error>
+        where D: Deserializer<'de>
+    {
+        let stored = deserializer.deserialize_struct("FormatSpec", FIELDS, StoredVisitor)?;
+        Ok(FormatSpecBuf {
+               options: stored.options,
+               format: stored.format.into_owned(),
+               zero_text: stored.zero_text.into_owned(),
+           })
+    }
+}
+
+// A spec read back, its format already checked, with the strings kept apart from the options.
+#[cfg(feature = "serde")]
+struct Stored<'de> {
+    options: FormatSpec<'static>,
+    format: Cow<'de, str>,
+    zero_text: Cow<'de, str>,
+}
+
+#[cfg(feature = "serde")]
+struct StoredVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for StoredVisitor {
+    type Value = Stored<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a FormatSpec struct")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Stored<'de>, A::Error>
+        where A: SeqAccess<'de>
+    {
+        let mut fields = Fields::default();
+        for (i, &name) in FIELDS.iter().enumerate() {
+            if !fields.read(name, &mut NextElement(&mut seq))? {
+                return Err(de::Error::invalid_length(i, &self));
+            }
+        }
+        fields.finish()
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Stored<'de>, A::Error>
+        where A: MapAccess<'de>
+    {
+        let mut fields = Fields::default();
+        while let Some(key) = map.next_key::<Text>()? {
+            let name = FIELDS.iter()
+                .find(|&&name| name == key.0)
+                .ok_or_else(|| de::Error::unknown_field(&key.0, FIELDS))?;
+            fields.read(name, &mut NextValue(&mut map))?;
+        }
+        fields.finish()
+    }
+}
+
+// The next field's value, from a sequence or a map.
+#[cfg(feature = "serde")]
+trait NextField<'de> {
+    type Error: de::Error;
+    fn next<T>(&mut self) -> Result<Option<T>, Self::Error> where T: Deserialize<'de>;
+}
+
+#[cfg(feature = "serde")]
+struct NextElement<'s, A: 's>(&'s mut A);
+
+#[cfg(feature = "serde")]
+impl<'s, 'de, A> NextField<'de> for NextElement<'s, A>
+    where A: SeqAccess<'de>
+{
+    type Error = A::Error;
+    fn next<T>(&mut self) -> Result<Option<T>, A::Error>
+        where T: Deserialize<'de>
+    {
+        self.0.next_element()
+    }
+}
+
+#[cfg(feature = "serde")]
+struct NextValue<'s, A: 's>(&'s mut A);
+
+#[cfg(feature = "serde")]
+impl<'s, 'de, A> NextField<'de> for NextValue<'s, A>
+    where A: MapAccess<'de>
+{
+    type Error = A::Error;
+    fn next<T>(&mut self) -> Result<Option<T>, A::Error>
+        where T: Deserialize<'de>
+    {
+        self.0.next_value().map(Some)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Default)]
+struct Fields<'de> {
+    format: Option<Cow<'de, str>>,
+    leading_zeros: Option<LeadingZeros>,
+    max_precision: Option<Unit>,
+    strict: Option<bool>,
+    numerals: Option<Numerals>,
+    bidi_isolates: Option<bool>,
+    beat_decimals: Option<u8>,
+    field_overflow: Option<FieldOverflow>,
+    missing_large_units: Option<MissingLargeUnits>,
+    zero_style: Option<(ZeroStyle<'static>, Cow<'de, str>)>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Fields<'de> {
+    // Reads the field `name`, `false` if the input has run out.
+    fn read<N>(&mut self, name: &'static str, next: &mut N) -> Result<bool, N::Error>
+        where N: NextField<'de>
+    {
+        let unknown = |kind: &str, name: &str| {
+            de::Error::custom(format_args!("unknown {} `{}`", kind, name))
+        };
+        let duplicate = match name {
+            "format" => {
+                match next.next::<Text>()? {
+                    Some(format) => self.format.replace(format.0).is_some(),
+                    None => return Ok(false),
+                }
+            }
+            "leading_zeros" => {
+                let name = match next.next::<Text>()? {
+                    Some(name) => name.0,
+                    None => return Ok(false),
+                };
+                let value = [LeadingZeros::All, LeadingZeros::ExceptLargest, LeadingZeros::None]
+                    .iter()
+                    .cloned()
+                    .find(|&value| leading_zeros_name(value) == name)
+                    .ok_or_else(|| unknown("leading zeros", &name))?;
+                self.leading_zeros.replace(value).is_some()
+            }
+            "max_precision" => {
+                let name = match next.next::<Text>()? {
+                    Some(name) => name.0,
+                    None => return Ok(false),
+                };
+                let unit = name.parse().map_err(|_| unknown("unit", &name))?;
+                self.max_precision.replace(unit).is_some()
+            }
+            "strict" => {
+                match next.next()? {
+                    Some(strict) => self.strict.replace(strict).is_some(),
+                    None => return Ok(false),
+                }
+            }
+            "numerals" => {
+                match next.next::<NumeralsName>()? {
+                    Some(numerals) => self.numerals.replace(numerals.0).is_some(),
+                    None => return Ok(false),
+                }
+            }
+            "bidi_isolates" => {
+                match next.next()? {
+                    Some(bidi_isolates) => self.bidi_isolates.replace(bidi_isolates).is_some(),
+                    None => return Ok(false),
+                }
+            }
+            "beat_decimals" => {
+                let decimals = match next.next::<u8>()? {
+                    Some(decimals) => decimals,
+                    None => return Ok(false),
+                };
+                if decimals > 9 {
+                    return Err(de::Error::custom("beat decimals out of range"));
+                }
+                self.beat_decimals.replace(decimals).is_some()
+            }
+            "field_overflow" => {
+                let name = match next.next::<Text>()? {
+                    Some(name) => name.0,
+                    None => return Ok(false),
+                };
+                let value = [FieldOverflow::Widen, FieldOverflow::Clamp, FieldOverflow::Error]
+                    .iter()
+                    .cloned()
+                    .find(|&value| field_overflow_name(value) == name)
+                    .ok_or_else(|| unknown("field overflow", &name))?;
+                self.field_overflow.replace(value).is_some()
+            }
+            "missing_large_units" => {
+                let name = match next.next::<Text>()? {
+                    Some(name) => name.0,
+                    None => return Ok(false),
+                };
+                let value = [MissingLargeUnits::Ignore, MissingLargeUnits::Prefix]
+                    .iter()
+                    .cloned()
+                    .find(|&value| missing_large_units_name(value) == name)
+                    .ok_or_else(|| unknown("missing large units", &name))?;
+                self.missing_large_units.replace(value).is_some()
+            }
+            "zero_style" => {
+                let parts = match next.next::<Option<(Text, Option<(u64, u32)>)>>()? {
+                    Some(parts) => parts,
+                    None => return Ok(false),
+                };
+                let zero_style = match parts {
+                    None => (ZeroStyle::Normal, Cow::Borrowed("")),
+                    Some((text, None)) => (ZeroStyle::Zero(""), text.0),
+                    Some((_, Some((_, nanos)))) if nanos >= 1_000_000_000 => {
+                        return Err(de::Error::invalid_value(Unexpected::Unsigned(nanos as u64),
+                                                            &"fewer than 10^9 nanoseconds"));
+                    }
+                    Some((text, Some((secs, nanos)))) => {
+                        (ZeroStyle::Below(Duration::new(secs, nanos), ""), text.0)
+                    }
+                };
+                self.zero_style.replace(zero_style).is_some()
+            }
+            _ => unreachable!("not a FormatSpec field"),
+        };
+        if duplicate {
+            return Err(de::Error::duplicate_field(name));
+        }
+        Ok(true)
+    }
+
+    fn finish<E>(self) -> Result<Stored<'de>, E>
+        where E: de::Error
+    {
+        fn required<T, E>(value: Option<T>, name: &'static str) -> Result<T, E>
+            where E: de::Error
+        {
+            value.ok_or_else(|| de::Error::missing_field(name))
+        }
+        let format = required(self.format, "format")?;
+        FormatSpec::new(&format)
+            .map_err(|e| de::Error::custom(format_args!("invalid format: {:?}", e)))?;
+        let (zero_style, zero_text) = required(self.zero_style, "zero_style")?;
+        Ok(Stored {
+               options: FormatSpec {
+                   format: "",
+                   leading_zeros: required(self.leading_zeros, "leading_zeros")?,
+                   max_precision: required(self.max_precision, "max_precision")?,
+                   strict: required(self.strict, "strict")?,
+                   numerals: required(self.numerals, "numerals")?,
+                   bidi_isolates: required(self.bidi_isolates, "bidi_isolates")?,
+                   beat_decimals: required(self.beat_decimals, "beat_decimals")?,
+                   field_overflow: required(self.field_overflow, "field_overflow")?,
+                   missing_large_units: required(self.missing_large_units,
+                                                 "missing_large_units")?,
+                   zero_style,
+               },
+               format,
+               zero_text,
+           })
+    }
+}
+
+// A string borrowed from the input when the deserializer allows it and copied otherwise.
+#[cfg(feature = "serde")]
+struct Text<'de>(Cow<'de, str>);
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Text<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Text<'de>, D::Error>
+        where D: Deserializer<'de>
+    {
+        deserializer.deserialize_str(TextVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct TextVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for TextVisitor {
+    type Value = Text<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E>(self, text: &'de str) -> Result<Text<'de>, E> {
+        Ok(Text(Cow::Borrowed(text)))
+    }
+    fn visit_str<E>(self, text: &str) -> Result<Text<'de>, E> {
+        Ok(Text(Cow::Owned(text.to_owned())))
+    }
+    fn visit_string<E>(self, text: String) -> Result<Text<'de>, E> {
+        Ok(Text(Cow::Owned(text)))
+    }
+}
+
+#[cfg(feature = "serde")]
+const NAMED_NUMERALS: [Numerals; 6] = [Numerals::Latin,
+                                       Numerals::ArabicIndic,
+                                       Numerals::ExtendedArabicIndic,
+                                       Numerals::Devanagari,
+                                       Numerals::Bengali,
+                                       Numerals::Thai];
+
+#[cfg(feature = "serde")]
+const NUMERALS_VARIANTS: &[&str] = &["latin", "arabic-indic", "extended-arabic-indic",
+                                     "devanagari", "bengali", "thai", "custom"];
+
+// `Numerals` as an externally tagged enum: `"thai"`, or `{"custom": "oizehsGtBg"}`.
+#[cfg(feature = "serde")]
+struct NumeralsName(Numerals);
+
+#[cfg(feature = "serde")]
+impl Serialize for NumeralsName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        match self.0 {
+            Numerals::Custom(digits) => {
+                let digits: String = digits.iter().collect();
+                serializer.serialize_newtype_variant("Numerals", 6, "custom", &digits)
+            }
+            numerals => {
+                let index = NAMED_NUMERALS.iter().position(|&n| n == numerals).unwrap_or(0);
+                serializer.serialize_unit_variant("Numerals",
+                                                  index as u32,
+                                                  NUMERALS_VARIANTS[index])
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for NumeralsName {
+    fn deserialize<D>(deserializer: D) -> Result<NumeralsName, D::Error>
+        where D: Deserializer<'de>
+    {
+        deserializer.deserialize_enum("Numerals", NUMERALS_VARIANTS, NumeralsVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct NumeralsVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for NumeralsVisitor {
+    type Value = NumeralsName;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a numeral system")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<NumeralsName, A::Error>
+        where A: EnumAccess<'de>
+    {
+        let (name, variant) = data.variant::<Text>()?;
+        let index = NUMERALS_VARIANTS.iter()
+            .position(|&variant| variant == name.0)
+            .ok_or_else(|| de::Error::unknown_variant(&name.0, NUMERALS_VARIANTS))?;
+        if let Some(&numerals) = NAMED_NUMERALS.get(index) {
+            variant.unit_variant()?;
+            return Ok(NumeralsName(numerals));
+        }
+
+        let text = variant.newtype_variant::<Text>()?.0;
+        let mut digits = ['0'; 10];
+        let mut chars = text.chars();
+        let wrong_length = || de::Error::custom("custom numerals need ten digits");
+        for digit in digits.iter_mut() {
+            *digit = chars.next().ok_or_else(wrong_length)?;
+        }
+        if chars.next().is_some() {
+            return Err(wrong_length());
+        }
+        Ok(NumeralsName(Numerals::Custom(digits)))
+    }
+}
+
+#[cfg(feature = "serde")]
+fn leading_zeros_name(leading_zeros: LeadingZeros) -> &'static str {
+    match leading_zeros {
+        LeadingZeros::All => "all",
+        LeadingZeros::ExceptLargest => "except-largest",
+        LeadingZeros::None => "none",
+    }
+}
+
+#[cfg(feature = "serde")]
+fn field_overflow_name(field_overflow: FieldOverflow) -> &'static str {
+    match field_overflow {
+        FieldOverflow::Widen => "widen",
+        FieldOverflow::Clamp => "clamp",
+        FieldOverflow::Error => "error",
+    }
+}
+
+#[cfg(feature = "serde")]
+fn missing_large_units_name(missing_large_units: MissingLargeUnits) -> &'static str {
+    match missing_large_units {
+        MissingLargeUnits::Ignore => "ignore",
+        MissingLargeUnits::Prefix => "prefix",
+    }
+}
+
+#[cfg(feature = "serde")]
+fn zero_style_parts<'a>(zero_style: ZeroStyle<'a>) -> Option<(&'a str, Option<(u64, u32)>)> {
+    match zero_style {
+        ZeroStyle::Normal => None,
+        ZeroStyle::Zero(text) => Some((text, None)),
+        ZeroStyle::Below(threshold, text) => {
+            Some((text, Some((threshold.as_secs(), threshold.subsec_nanos()))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_spec_buf() {
+        let format = String::from("%M:%S");
+        let buf = FormatSpecBuf::from(FormatSpec::new(&format)
+                                          .unwrap()
+                                          .with_zero_style(ZeroStyle::Zero("-")));
+        drop(format);
+        assert_eq!(buf.as_str(), "%M:%S");
+        assert_eq!(buf.spec().zero_style(), ZeroStyle::Zero("-"));
+        let time = ::std::time::Duration::from_secs(65);
+        assert_eq!(buf.spec().with_time(time).unwrap().to_string(), "01:05");
+        assert_eq!(FormatSpecBuf::new("%Q"), Err(FormatError::UnknownField));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+
+        let spec = FormatSpec::new("%H:%M")
+            .unwrap()
+            .with_leading_zeros(LeadingZeros::ExceptLargest)
+            .with_numerals(Numerals::Custom(['o', 'i', 'z', 'e', 'h', 's', 'G', 't', 'B', 'g']))
+            .with_zero_style(ZeroStyle::Below(Duration::from_millis(1), "<1ms"));
+        let tokens = |format: Token, numerals: &[Token]| {
+            let mut tokens = vec![Token::Struct {
+                                      name: "FormatSpec",
+                                      len: 10,
+                                  },
+                                  Token::Str("format"),
+                                  format,
+                                  Token::Str("leading_zeros"),
+                                  Token::BorrowedStr("except-largest"),
+                                  Token::Str("max_precision"),
+                                  Token::BorrowedStr("nanoseconds"),
+                                  Token::Str("strict"),
+                                  Token::Bool(false),
+                                  Token::Str("numerals")];
+            tokens.extend_from_slice(numerals);
+            tokens.extend_from_slice(&[Token::Str("bidi_isolates"),
+                                       Token::Bool(false),
+                                       Token::Str("beat_decimals"),
+                                       Token::U8(2),
+                                       Token::Str("field_overflow"),
+                                       Token::BorrowedStr("widen"),
+                                       Token::Str("missing_large_units"),
+                                       Token::BorrowedStr("ignore"),
+                                       Token::Str("zero_style"),
+                                       Token::Some,
+                                       Token::Tuple { len: 2 },
+                                       Token::BorrowedStr("<1ms"),
+                                       Token::Some,
+                                       Token::Tuple { len: 2 },
+                                       Token::U64(0),
+                                       Token::U32(1_000_000),
+                                       Token::TupleEnd,
+                                       Token::TupleEnd,
+                                       Token::StructEnd]);
+            tokens
+        };
+        let custom = [Token::NewtypeVariant {
+                          name: "Numerals",
+                          variant: "custom",
+                      },
+                      Token::BorrowedStr("oizehsGtBg")];
+        assert_tokens(&spec, &tokens(Token::BorrowedStr("%H:%M"), &custom));
+        assert_tokens(&FormatSpecBuf::from(spec), &tokens(Token::BorrowedStr("%H:%M"), &custom));
+        assert_de_tokens(&FormatSpecBuf::from(spec), &tokens(Token::String("%H:%M"), &custom));
+        assert_de_tokens_error::<FormatSpec>(&tokens(Token::String("%H:%M"), &custom),
+                                             "string can't be borrowed from the input, read a \
+                                              FormatSpecBuf instead");
+        assert_de_tokens_error::<FormatSpecBuf>(&tokens(Token::BorrowedStr("%Q"), &custom),
+                                                "invalid format: UnknownField");
+
+        let devanagari = [Token::UnitVariant {
+                              name: "Numerals",
+                              variant: "devanagari",
+                          }];
+        assert_de_tokens(&spec.with_numerals(Numerals::Devanagari),
+                         &tokens(Token::BorrowedStr("%H:%M"), &devanagari));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_reader() {
+        use serde_json;
+
+        let spec = FormatSpec::new("%H:%M").unwrap().with_numerals(Numerals::Thai);
+        let json = serde_json::to_string(&spec).unwrap();
+        assert_eq!(json,
+                   concat!(r#"{"format":"%H:%M","leading_zeros":"all","#,
+                           r#""max_precision":"nanoseconds","strict":false,"numerals":"thai","#,
+                           r#""bidi_isolates":false,"beat_decimals":2,"field_overflow":"widen","#,
+                           r#""missing_large_units":"ignore","zero_style":null}"#));
+        assert_eq!(serde_json::from_str::<FormatSpec>(&json).unwrap(), spec);
+        let read: FormatSpecBuf = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(read.spec(), spec);
+
+        let escaped = json.replace("%H", "\\u0025H");
+        assert_eq!(serde_json::from_str::<FormatSpecBuf>(&escaped).unwrap().spec(), spec);
+        assert!(serde_json::from_str::<FormatSpec>(&escaped).is_err());
+
+        let custom = json.replace(r#""thai""#, r#"{"custom":"devanagari"}"#);
+        assert_eq!(serde_json::from_str::<FormatSpec>(&custom).unwrap().numerals(),
+                   Numerals::Custom(['d', 'e', 'v', 'a', 'n', 'a', 'g', 'a', 'r', 'i']));
+        let short = json.replace(r#""thai""#, r#"{"custom":"0123"}"#);
+        assert!(serde_json::from_str::<FormatSpec>(&short).is_err());
+
+        let nanos = json.replace(r#""zero_style":null"#, r#""zero_style":["<1s",[0,1000000000]]"#);
+        assert!(serde_json::from_str::<FormatSpec>(&nanos)
+                    .unwrap_err()
+                    .to_string()
+                    .contains("fewer than 10^9 nanoseconds"));
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt::Write;
+use std::mem;
+
+use decomposed::Decompose;
+use super::{FormatError, FormatSpec};
+
+// What changed between two consecutive renders: keep the first `unchanged` characters of the
+// previous output, erase the `erased` characters after them, then write `text`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Redraw<'a> {
+    pub unchanged: usize,
+    pub erased: usize,
+    pub text: &'a str,
+}
+
+// Renders a ticking duration (a stopwatch, a progress line) and reports only the suffix that
+// differs from the previous render, so a terminal can redraw a few characters per frame
+// instead of the whole line.
+#[derive(Clone, Debug)]
+pub struct StreamFormatter<'a> {
+    spec: FormatSpec<'a>,
+    last: String,
+    next: String,
+}
+
+impl<'a> StreamFormatter<'a> {
+    pub fn new(spec: FormatSpec<'a>) -> StreamFormatter<'a> {
+        StreamFormatter {
+            spec,
+            last: String::new(),
+            next: String::new(),
+        }
+    }
+
+    pub fn spec(&self) -> FormatSpec<'a> {
+        self.spec
+    }
+    pub fn last(&self) -> &str {
+        &self.last
+    }
+    pub fn reset(&mut self) {
+        self.last.clear();
+    }
+
+    pub fn update<D>(&mut self, time: D) -> Result<Redraw<'_>, FormatError>
+        where D: Decompose
+    {
+        self.next.clear();
+        write!(self.next, "{}", self.spec.with_time(time)?)?;
+        mem::swap(&mut self.last, &mut self.next);
+
+        let mut unchanged = 0;
+        let mut split = 0;
+        for ((offset, new), old) in self.last.char_indices().zip(self.next.chars()) {
+            if new != old {
+                break;
+            }
+            unchanged += 1;
+            split = offset + new.len_utf8();
+        }
+        Ok(Redraw {
+            unchanged,
+            erased: self.next.chars().count() - unchanged,
+            text: &self.last[split..],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_update() {
+        let mut stream = StreamFormatter::new(FormatSpec::new("%M:%S.%x").unwrap());
+        assert_eq!(stream.update(Duration::from_millis(59_990)).unwrap(),
+                   Redraw {
+                       unchanged: 0,
+                       erased: 0,
+                       text: "00:59.990",
+                   });
+        assert_eq!(stream.update(Duration::from_millis(59_995)).unwrap(),
+                   Redraw {
+                       unchanged: 8,
+                       erased: 1,
+                       text: "5",
+                   });
+        assert_eq!(stream.update(Duration::from_millis(60_000)).unwrap(),
+                   Redraw {
+                       unchanged: 1,
+                       erased: 8,
+                       text: "1:00.000",
+                   });
+        assert_eq!(stream.update(Duration::from_millis(60_000)).unwrap().text, "");
+        assert_eq!(stream.last(), "01:00.000");
+
+        stream.reset();
+        assert_eq!(stream.update(Duration::from_millis(60_000)).unwrap().unchanged, 0);
+    }
+}
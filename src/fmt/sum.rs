@@ -0,0 +1,76 @@
+use std::fmt;
+
+use decomposed::{Decompose, DecomposedTime};
+use super::{format_duration, validate_format, FormatError};
+use units::NANOS_PER_SEC;
+
+// A total and each of its items rendered with the same format. Displays as the invoice line
+// "00:12:03 + 00:05:00 + 01:00:00 = 01:17:03"; with no items it is just the total.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sum {
+    pub items: Vec<String>,
+    pub total: String,
+}
+
+// Adds up `times`, negative ones subtracting, and formats the total and every item with `format`.
+pub fn sum_and_format<I, D>(times: I, format: &str) -> Result<Sum, FormatError>
+    where I: IntoIterator<Item = D>,
+          D: Decompose
+{
+    validate_format(format)?;
+    let mut items = Vec::new();
+    let mut total: i128 = 0;
+    for time in times {
+        let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+        let nanos = time.total_nanoseconds()
+            .filter(|&nanos| nanos <= i128::MAX as u128)
+            .ok_or(FormatError::ValueOutOfRange)? as i128;
+        let nanos = if time.is_negative() { -nanos } else { nanos };
+        total = total.checked_add(nanos).ok_or(FormatError::ValueOutOfRange)?;
+        items.push(format_duration(format, time)?);
+    }
+
+    let secs = total.unsigned_abs() / NANOS_PER_SEC as u128;
+    if secs > u64::MAX as u128 {
+        return Err(FormatError::ValueOutOfRange);
+    }
+    let subsec_nanos = (total.unsigned_abs() % NANOS_PER_SEC as u128) as u32;
+    let total = DecomposedTime::from_secs_nanos(secs as u64, subsec_nanos, total >= 0);
+    Ok(Sum {
+           items,
+           total: format_duration(format, total)?,
+       })
+}
+
+impl fmt::Display for Sum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.items.is_empty() {
+            write!(f, "{} = ", self.items.join(" + "))?;
+        }
+        f.write_str(&self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sum_and_format() {
+        let calls = [Duration::from_secs(723), Duration::from_secs(300), Duration::from_secs(3600)];
+        let sum = sum_and_format(calls.iter().cloned(), "%H:%M:%S").unwrap();
+        assert_eq!(sum.items, vec!["00:12:03", "00:05:00", "01:00:00"]);
+        assert_eq!(format!("{} calls: {}", sum.items.len(), sum),
+                   "3 calls: 00:12:03 + 00:05:00 + 01:00:00 = 01:17:03");
+
+        let refund = DecomposedTime::new(0, 0, 0, 20, 0, 0.0, false);
+        let sum = sum_and_format(vec![DecomposedTime::new(0, 1, 0, 0, 0, 0.0, true), refund],
+                                 "%H:%M")
+            .unwrap();
+        assert_eq!(sum.total, "23:40");
+        assert_eq!(sum_and_format(Vec::<Duration>::new(), "%M").unwrap().to_string(), "00");
+        assert_eq!(sum_and_format(calls.iter().cloned(), "%Q").unwrap_err(),
+                   FormatError::UnknownField);
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::time::Duration;
+
+use decomposed::{self, Decompose, DecomposedTime, Rounding};
+use units::{self, Unit};
+use super::FormatError;
+
+const MAX_PAYROLL_DECIMALS: usize = 4;
+
+// The duration as a decimal count of one unit with a fixed number of decimals, "1.50 h", for
+// spreadsheets and timesheets. The last decimal is rounded half away from zero, and
+// `with_grouping` separates the thousands of the whole part, "86,400.000 s". `new` fails with
+// `ValueOutOfRange` when the value times 10^precision doesn't fit in a u128, so `Display` can't.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitDecimal {
+    time: DecomposedTime,
+    unit: Unit,
+    precision: usize,
+    grouping: Option<char>,
+    scaled: u128,
+}
+
+// Timesheet hours: the duration rounded to a multiple of `increment` (six minutes, a tenth of an
+// hour, by default) and written as decimal hours, "7.5 h". Gets as many decimals as the
+// increment needs, up to four; increments that aren't a terminating decimal of an hour, such as
+// one minute, get two. A zero increment makes `format` fail with `ValueOutOfRange`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Payroll {
+    increment: Duration,
+    rounding: Rounding,
+}
+
+pub fn as_decimal<D>(time: D, unit: Unit, precision: usize) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    Ok(UnitDecimal::new(time, unit, precision)?.to_string())
+}
+
+// Total seconds with exactly `precision` decimals and never another unit, for latency reports
+// whose columns must not switch units: "86,400.000 s" with `Some(',')` as the grouping.
+pub fn format_fixed_seconds<D>(time: D,
+                               precision: usize,
+                               grouping: Option<char>)
+                               -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    Ok(UnitDecimal::new(time, Unit::Seconds, precision)?.with_grouping(grouping).to_string())
+}
+
+pub fn format_payroll<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    Payroll::new().format(time)
+}
+
+impl Payroll {
+    pub fn new() -> Payroll {
+        Payroll {
+            increment: Duration::from_secs(6 * units::SECS_PER_MINUTE),
+            rounding: Rounding::Nearest,
+        }
+    }
+    #[must_use]
+    pub fn with_increment(mut self, increment: Duration) -> Payroll {
+        self.increment = increment;
+        self
+    }
+    #[must_use]
+    pub fn with_rounding(mut self, rounding: Rounding) -> Payroll {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+    pub fn rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    pub fn format<D>(&self, time: D) -> Result<String, FormatError>
+        where D: Decompose
+    {
+        let time = decomposed::quantize(time, self.increment, self.rounding)?;
+        as_decimal(time, Unit::Hours, self.decimals())
+    }
+
+    fn decimals(&self) -> usize {
+        let (increment, hour) = (self.increment.as_nanos(), units::NANOS_PER_HOUR as u128);
+        let exact = |decimals: usize| {
+            (increment * 10u128.pow(decimals as u32)).checked_rem(hour) == Some(0)
+        };
+        (0..MAX_PAYROLL_DECIMALS + 1).find(|&decimals| exact(decimals)).unwrap_or(2)
+    }
+}
+
+impl Default for Payroll {
+    fn default() -> Payroll {
+        Payroll::new()
+    }
+}
+
+impl UnitDecimal {
+    pub fn new(time: DecomposedTime,
+               unit: Unit,
+               precision: usize)
+               -> Result<UnitDecimal, FormatError> {
+        let scaled = scale(&time, unit, precision).ok_or(FormatError::ValueOutOfRange)?;
+        Ok(UnitDecimal {
+            time,
+            unit,
+            precision,
+            grouping: None,
+            scaled,
+        })
+    }
+    #[must_use]
+    pub fn with_grouping(mut self, grouping: Option<char>) -> UnitDecimal {
+        self.grouping = grouping;
+        self
+    }
+    pub fn time(&self) -> &DecomposedTime {
+        &self.time
+    }
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+    pub fn precision(&self) -> usize {
+        self.precision
+    }
+    pub fn grouping(&self) -> Option<char> {
+        self.grouping
+    }
+}
+
+// The rounded value times 10^precision, or `None` if that doesn't fit in a u128.
+fn scale(time: &DecomposedTime, unit: Unit, precision: usize) -> Option<u128> {
+    let size = unit.nanoseconds() as u128;
+    let scale = 10u128.checked_pow(precision as u32)?;
+    let nanos = time.total_nanoseconds()?.checked_mul(scale)?;
+    Some(nanos / size + if nanos % size * 2 >= size { 1 } else { 0 })
+}
+
+impl fmt::Display for UnitDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scaled = self.scaled;
+        if self.time.is_negative() && scaled > 0 {
+            f.write_str("-")?;
+        }
+        let scale = 10u128.pow(self.precision as u32);
+        let whole = (scaled / scale).to_string();
+        match self.grouping {
+            Some(separator) => {
+                for (i, digit) in whole.chars().enumerate() {
+                    if i > 0 && (whole.len() - i).checked_rem(3) == Some(0) {
+                        write!(f, "{}", separator)?;
+                    }
+                    write!(f, "{}", digit)?;
+                }
+            }
+            None => f.write_str(&whole)?,
+        }
+        if self.precision > 0 {
+            write!(f, ".{:01$}", scaled % scale, self.precision)?;
+        }
+        write!(f, " {}", self.unit.symbol())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_decimal() {
+        assert_eq!(as_decimal(Duration::from_secs(90 * 60), Unit::Hours, 2).unwrap(), "1.50 h");
+        assert_eq!(as_decimal(Duration::from_secs(54 * 3600), Unit::Days, 2).unwrap(), "2.25 d");
+        assert_eq!(as_decimal(Duration::from_secs(20 * 60), Unit::Hours, 2).unwrap(), "0.33 h");
+        assert_eq!(as_decimal(Duration::from_secs(40 * 60), Unit::Hours, 2).unwrap(), "0.67 h");
+        assert_eq!(as_decimal(Duration::from_secs(45 * 60), Unit::Hours, 1).unwrap(), "0.8 h");
+        assert_eq!(as_decimal(Duration::from_secs(89 * 60), Unit::Hours, 0).unwrap(), "1 h");
+        let negative = DecomposedTime::from_secs_nanos(1, 500_000_000, false);
+        assert_eq!(as_decimal(negative, Unit::Milliseconds, 0).unwrap(), "-1500 ms");
+        assert_eq!(as_decimal(Duration::from_secs(0), Unit::Hours, 1).unwrap(), "0.0 h");
+        assert_eq!(as_decimal(Duration::from_secs(1), Unit::Hours, 40),
+                   Err(FormatError::ValueOutOfRange));
+    }
+
+    #[test]
+    fn test_fixed_seconds() {
+        assert_eq!(format_fixed_seconds(Duration::from_secs(86_400), 3, Some(',')).unwrap(),
+                   "86,400.000 s");
+        assert_eq!(format_fixed_seconds(Duration::from_micros(1500), 3, Some(',')).unwrap(),
+                   "0.002 s");
+        assert_eq!(format_fixed_seconds(Duration::from_secs(999), 1, Some(',')).unwrap(),
+                   "999.0 s");
+        let negative = DecomposedTime::from_secs_nanos(300 * 3600, 0, false);
+        assert_eq!(format_fixed_seconds(negative, 0, Some('\u{202f}')).unwrap(),
+                   "-1\u{202f}080\u{202f}000 s");
+        assert_eq!(format_fixed_seconds(Duration::from_secs(86_400), 2, None).unwrap(),
+                   "86400.00 s");
+    }
+
+    #[test]
+    fn test_payroll() {
+        let shift = Duration::from_secs(7 * 3600 + 28 * 60);
+        assert_eq!(format_payroll(shift).unwrap(), "7.5 h");
+        assert_eq!(format_payroll(Duration::from_secs(8 * 3600)).unwrap(), "8.0 h");
+        assert_eq!(Payroll::new().with_rounding(Rounding::TowardZero).format(shift).unwrap(),
+                   "7.4 h");
+
+        let quarters = Payroll::new().with_increment(Duration::from_secs(15 * 60));
+        assert_eq!(quarters.format(shift).unwrap(), "7.50 h");
+        assert_eq!(quarters.format(Duration::from_secs(52 * 60)).unwrap(), "0.75 h");
+        assert_eq!(Payroll::new()
+                       .with_increment(Duration::from_secs(60))
+                       .format(Duration::from_secs(20 * 60))
+                       .unwrap(),
+                   "0.33 h");
+        assert_eq!(Payroll::new()
+                       .with_increment(Duration::from_secs(3600))
+                       .format(shift)
+                       .unwrap(),
+                   "7 h");
+        assert_eq!(Payroll::new().with_increment(Duration::from_secs(0)).format(shift),
+                   Err(FormatError::ValueOutOfRange));
+    }
+}
@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use units::{NANOS_PER_DAY, NANOS_PER_HOUR, NANOS_PER_MINUTE, NANOS_PER_SEC, NANOS_PER_YEAR};
+use super::{has_field, parse_cycle_field, FieldOverflow, FormatSpec, LeadingZeros,
+            MissingLargeUnits, ZeroStyle, FIELD_DELIMITER, NANOS_PER_BEAT};
+
+// The durations `width_bounds` sizes a column for, before any of them is seen. `max` is the
+// longest magnitude expected, `None` for no limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct WidthLimits {
+    max: Option<Duration>,
+}
+
+// The fewest and most chars of some output, `None` when there is no upper limit.
+type Width = (usize, Option<usize>);
+
+impl WidthLimits {
+    pub const fn new() -> WidthLimits {
+        WidthLimits { max: None }
+    }
+    #[must_use]
+    pub const fn with_max(mut self, max: Duration) -> WidthLimits {
+        self.max = Some(max);
+        self
+    }
+    pub const fn max(&self) -> Option<Duration> {
+        self.max
+    }
+}
+
+impl<'a> FormatSpec<'a> {
+    // The fewest and most chars this spec renders for the durations `limits` allows, so table
+    // layouts and fixed-width logs can reserve a column up front. The maximum is `None` when a
+    // field has no limit, such as `%Y` or `%T` without `with_max`. Directional isolates count as
+    // chars and presets leave room for a sign. Each field is bounded on its own, so a format
+    // whose fields can't all be at their extremes at once stays inside the bounds.
+    pub fn width_bounds(&self, limits: &WidthLimits) -> (usize, Option<usize>) {
+        let limits = Limits(limits.max().map(|max| max.as_nanos()));
+        let mut width = (0, Some(0));
+        let mut chars = self.format.chars();
+        let mut largest = true;
+        let mut isolated = false;
+
+        if self.missing_large_units == MissingLargeUnits::Prefix {
+            if !has_field(self.format, &['Y', 'K', 'G', 'T', 'U', '@']) {
+                let years = limits.total(NANOS_PER_YEAR);
+                width = add(width, (0, years.map(|years| prefix(years, 3))));
+            }
+            if !has_field(self.format, &['D', 'T', 'U', '@']) {
+                width = add(width, (0, Some(prefix(limits.field(NANOS_PER_DAY, 364), 2))));
+            }
+        }
+
+        while let Some(ch) = chars.next() {
+            if ch != FIELD_DELIMITER {
+                if isolated && !ch.is_ascii_punctuation() {
+                    width = add(width, (1, Some(1)));
+                    isolated = false;
+                }
+                width = add(width, (1, Some(1)));
+                continue;
+            }
+            let field = match chars.next() {
+                Some(FIELD_DELIMITER) => {
+                    width = add(width, (1, Some(1)));
+                    continue;
+                }
+                Some(field) => field,
+                None => break,
+            };
+            if self.bidi_isolates && !isolated {
+                width = add(width, (1, Some(1)));
+                isolated = true;
+            }
+
+            let (field, field_width) = match field {
+                '(' => {
+                    let (field, cycle) = parse_cycle_field(&mut chars)
+                        .expect("format was validated by FormatSpec::new");
+                    (field, self.cycle_width(field, cycle, largest, &limits))
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').unwrap_or(rest.len());
+                    let leading_zeros = self.leading_zeros.for_clock(largest);
+                    let preset = preset_width(&rest[..end], leading_zeros, &limits);
+                    chars = rest[(end + 1).min(rest.len())..].chars();
+                    (field, preset)
+                }
+                '1'..='9' => {
+                    let padding = field as usize - '0' as usize;
+                    let field = chars.next().unwrap_or('D');
+                    match field {
+                        'f' => (field, (2 + padding, Some(2 + padding))),
+                        field => (field, self.padded(field_max(field, &limits), padding)),
+                    }
+                }
+                field => (field, self.field_width(field, largest, &limits)),
+            };
+            width = add(width, field_width);
+            largest &= !matches!(field, 'H' | 'M' | 'S');
+        }
+        if isolated {
+            width = add(width, (1, Some(1)));
+        }
+
+        match self.zero_style {
+            ZeroStyle::Normal => width,
+            ZeroStyle::Zero(text) |
+            ZeroStyle::Below(_, text) => {
+                let text = text.chars().count();
+                (width.0.min(text), width.1.map(|max| max.max(text)))
+            }
+        }
+    }
+
+    fn field_width(&self, field: char, largest: bool, limits: &Limits) -> Width {
+        let max = field_max(field, limits);
+        match field {
+            'x' | 'y' | 'z' => self.padded(max, 3),
+            'S' | 'M' | 'H' => self.padded(max, self.leading_zeros.width(largest)),
+            'K' => (1, max.map(|years| decimal_width(years, 3))),
+            'G' => (1, max.map(|years| decimal_width(years, 9))),
+            'f' => (1, Some(11)),
+            'F' => (7, Some(7)),
+            '@' => {
+                let decimals = match self.beat_decimals {
+                    0 => 0,
+                    decimals => 1 + decimals as usize,
+                };
+                (1 + decimals, max.map(|beats| digits(beats) + decimals))
+            }
+            _ => (1, max.map(digits)),
+        }
+    }
+
+    fn cycle_width(&self, field: char, cycle: u64, largest: bool, limits: &Limits) -> Width {
+        let padding = match field {
+            'x' | 'y' | 'z' => 3,
+            'S' | 'M' | 'H' => self.leading_zeros.width(largest),
+            _ => 0,
+        };
+        let last = cycle as u128 - 1;
+        let max = field_max(field, limits).map_or(last, |max| max.min(last));
+        (padding.max(1), Some(digits(max).max(padding)))
+    }
+
+    // A field zero-padded to `padding` chars whose value is at most `max`.
+    fn padded(&self, max: Option<u128>, padding: usize) -> Width {
+        let widest = match self.field_overflow {
+            FieldOverflow::Widen => max.map(|max| digits(max).max(padding)),
+            _ if padding == 0 => max.map(digits),
+            _ => Some(padding),
+        };
+        (padding.max(1), widest)
+    }
+}
+
+// The longest duration allowed, in nanoseconds.
+struct Limits(Option<u128>);
+
+impl Limits {
+    // The most whole `unit`s (given in nanoseconds) in a duration.
+    fn total(&self, unit: u64) -> Option<u128> {
+        self.0.map(|max| max / unit as u128)
+    }
+    // The largest value of a field that carries into the next unit above `cap`.
+    fn field(&self, unit: u64, cap: u128) -> u128 {
+        self.total(unit).map_or(cap, |total| total.min(cap))
+    }
+}
+
+fn field_max(field: char, limits: &Limits) -> Option<u128> {
+    match field {
+        'x' | 'y' | 'z' => Some(999),
+        'S' | 's' => Some(limits.field(NANOS_PER_SEC, 59)),
+        'M' | 'm' => Some(limits.field(NANOS_PER_MINUTE, 59)),
+        'H' | 'h' => Some(limits.field(NANOS_PER_HOUR, 23)),
+        'D' => Some(limits.field(NANOS_PER_DAY, 364)),
+        'T' => limits.total(NANOS_PER_HOUR),
+        'U' => limits.total(NANOS_PER_DAY),
+        '@' => limits.total(NANOS_PER_BEAT as u64),
+        _ => limits.total(NANOS_PER_YEAR),
+    }
+}
+
+// Presets write their own sign, and the parts before the clock only when they are non-zero.
+fn preset_width(preset: &str, leading_zeros: LeadingZeros, limits: &Limits) -> Width {
+    let days = limits.field(NANOS_PER_DAY, 364);
+    match preset {
+        // "-1yr 2d 03:04:05.678'901'234", and at least "0:0" for zero
+        "clock" => {
+            let years = limits.total(NANOS_PER_YEAR);
+            let shortest = leading_zeros.width(true).max(1) + 1 + leading_zeros.width(false).max(1);
+            (shortest, years.map(|years| 1 + prefix(years, 3) + prefix(days, 2) + 8 + 12))
+        }
+        // "-8811h4m5s678ms901us234ns"
+        "compact" => (2, limits.total(NANOS_PER_HOUR).map(|hours| 1 + prefix(hours, 1) + 21)),
+        // "-367d 1:30:00"
+        "decimal" => (7, limits.total(NANOS_PER_DAY).map(|days| 1 + prefix(days, 2) + 7)),
+        // "-999.999ms"
+        _ => (3, limits.total(NANOS_PER_SEC).map(|secs| 1 + digits(secs).max(3) + 6)),
+    }
+}
+
+fn add(width: Width, other: Width) -> Width {
+    (width.0 + other.0, width.1.and_then(|max| other.1.map(|other| max + other)))
+}
+
+// A count written with `suffix` chars after it, or left out when it is zero.
+fn prefix(value: u128, suffix: usize) -> usize {
+    match value {
+        0 => 0,
+        value => digits(value) + suffix,
+    }
+}
+
+// `years` divided by 10^`decimals` as `write_float` prints it.
+fn decimal_width(years: u128, decimals: u32) -> usize {
+    match years {
+        0 => 1,
+        years => digits(years / 10u128.pow(decimals)) + 1 + decimals as usize,
+    }
+}
+
+fn digits(mut value: u128) -> usize {
+    let mut digits = 1;
+    while value >= 10 {
+        value /= 10;
+        digits += 1;
+    }
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_width_bounds() {
+        let unbounded = WidthLimits::new();
+        let two_days = WidthLimits::new().with_max(Duration::from_secs(2 * 86_400));
+        let spec = |format| FormatSpec::new(format).unwrap();
+
+        assert_eq!(spec("%H:%M:%S").width_bounds(&unbounded), (8, Some(8)));
+        assert_eq!(spec("%H:%M")
+                       .with_leading_zeros(LeadingZeros::ExceptLargest)
+                       .width_bounds(&unbounded),
+                   (4, Some(5)));
+        assert_eq!(spec("%T:%M").width_bounds(&unbounded), (4, None));
+        assert_eq!(spec("%T:%M").width_bounds(&two_days), (4, Some(5)));
+        assert_eq!(spec("%(T%12)h").width_bounds(&unbounded), (2, Some(3)));
+        assert_eq!(spec("%s s").with_zero_style(ZeroStyle::Zero("-")).width_bounds(&unbounded),
+                   (1, Some(4)));
+        assert_eq!(spec("%m min").with_bidi_isolates(true).width_bounds(&unbounded),
+                   (7, Some(8)));
+        assert_eq!(spec("%3Y").with_field_overflow(FieldOverflow::Clamp).width_bounds(&unbounded),
+                   (3, Some(3)));
+        let clock = |leading_zeros| {
+            spec("%{clock}").with_leading_zeros(leading_zeros).width_bounds(&unbounded).0
+        };
+        assert_eq!(clock(LeadingZeros::All), 5);
+        assert_eq!(clock(LeadingZeros::ExceptLargest), 4);
+        assert_eq!(clock(LeadingZeros::None), 3);
+    }
+
+    #[test]
+    fn test_width_bounds_hold() {
+        let format = "%Dd %H:%M:%S.%x'%y'%z %f %F %@ %U %{clock} %{compact} %{decimal} %{si}";
+        let max = Duration::new(2 * 86_400, 999_999_999);
+        let times = [Duration::from_secs(0),
+                     Duration::from_nanos(1),
+                     Duration::from_millis(59_999),
+                     Duration::new(86_399, 999_999_999),
+                     max];
+        let leading_zeros = [LeadingZeros::All, LeadingZeros::ExceptLargest, LeadingZeros::None];
+        for &leading_zeros in &leading_zeros {
+            let spec = FormatSpec::new(format).unwrap().with_leading_zeros(leading_zeros);
+            let (min, widest) = spec.width_bounds(&WidthLimits::new().with_max(max));
+            for &time in &times {
+                let width = spec.with_time(time).unwrap().to_string().chars().count();
+                assert!(min <= width && Some(width) <= widest, "{:?}: {}", time, width);
+            }
+        }
+    }
+}
@@ -0,0 +1,1333 @@
+use std::time;
+
+use decomposed::{Decompose, DecomposedTime};
+use fmt::{CompactDuration, FormatError, UnitSpacing, ZeroStyle};
+use units::{self, Unit};
+
+const DAYS_PER_YEAR: f64 = 365.0;
+const DAYS_PER_MONTH: f64 = 30.5;
+const DIGIT_WORDS: [&str; 10] = ["zero", "one", "two", "three", "four", "five", "six", "seven",
+                                 "eight", "nine"];
+
+// The units `largest_unit_only` picks from, largest first.
+const WHOLE_UNITS: [(Unit, u64); 5] = [(Unit::Years, units::NANOS_PER_YEAR),
+                                       (Unit::Days, units::NANOS_PER_DAY),
+                                       (Unit::Hours, units::NANOS_PER_HOUR),
+                                       (Unit::Minutes, units::NANOS_PER_MINUTE),
+                                       (Unit::Seconds, units::NANOS_PER_SEC)];
+
+// The units the humanize formatters write, smallest first.
+const UNITS: [Unit; 8] = [Unit::Microseconds,
+                          Unit::Milliseconds,
+                          Unit::Seconds,
+                          Unit::Minutes,
+                          Unit::Hours,
+                          Unit::Days,
+                          Unit::Months,
+                          Unit::Years];
+
+// Every unit `Abbreviations` reads, smallest first.
+const ABBREVIATED_UNITS: [Unit; 9] = [Unit::Nanoseconds,
+                                      Unit::Microseconds,
+                                      Unit::Milliseconds,
+                                      Unit::Seconds,
+                                      Unit::Minutes,
+                                      Unit::Hours,
+                                      Unit::Days,
+                                      Unit::Months,
+                                      Unit::Years];
+
+// The English singular and plural of each unit, which `Labels::new` starts from.
+fn unit_names(unit: Unit) -> (&'static str, &'static str) {
+    match unit {
+        Unit::Years => ("year", "years"),
+        Unit::Months => ("month", "months"),
+        Unit::Days => ("day", "days"),
+        Unit::Hours => ("hour", "hours"),
+        Unit::Minutes => ("minute", "minutes"),
+        Unit::Seconds => ("second", "seconds"),
+        Unit::Milliseconds => ("millisecond", "milliseconds"),
+        Unit::Microseconds => ("microsecond", "microseconds"),
+        Unit::Nanoseconds => ("nanosecond", "nanoseconds"),
+    }
+}
+
+// The singular and plural of each unit, in `Unit` order, for the languages
+// `Labels::for_language` knows besides English.
+fn localized_names(language: &str) -> Option<[(&'static str, &'static str); 9]> {
+    Some(match language {
+             "de" => [("Jahr", "Jahre"),
+                      ("Monat", "Monate"),
+                      ("Tag", "Tage"),
+                      ("Stunde", "Stunden"),
+                      ("Minute", "Minuten"),
+                      ("Sekunde", "Sekunden"),
+                      ("Millisekunde", "Millisekunden"),
+                      ("Mikrosekunde", "Mikrosekunden"),
+                      ("Nanosekunde", "Nanosekunden")],
+             "fr" => [("an", "ans"),
+                      ("mois", "mois"),
+                      ("jour", "jours"),
+                      ("heure", "heures"),
+                      ("minute", "minutes"),
+                      ("seconde", "secondes"),
+                      ("milliseconde", "millisecondes"),
+                      ("microseconde", "microsecondes"),
+                      ("nanoseconde", "nanosecondes")],
+             "es" => [("año", "años"),
+                      ("mes", "meses"),
+                      ("día", "días"),
+                      ("hora", "horas"),
+                      ("minuto", "minutos"),
+                      ("segundo", "segundos"),
+                      ("milisegundo", "milisegundos"),
+                      ("microsegundo", "microsegundos"),
+                      ("nanosegundo", "nanosegundos")],
+             "it" => [("anno", "anni"),
+                      ("mese", "mesi"),
+                      ("giorno", "giorni"),
+                      ("ora", "ore"),
+                      ("minuto", "minuti"),
+                      ("secondo", "secondi"),
+                      ("millisecondo", "millisecondi"),
+                      ("microsecondo", "microsecondi"),
+                      ("nanosecondo", "nanosecondi")],
+             "pt" => [("ano", "anos"),
+                      ("mês", "meses"),
+                      ("dia", "dias"),
+                      ("hora", "horas"),
+                      ("minuto", "minutos"),
+                      ("segundo", "segundos"),
+                      ("milissegundo", "milissegundos"),
+                      ("microssegundo", "microssegundos"),
+                      ("nanossegundo", "nanossegundos")],
+             "nl" => [("jaar", "jaar"),
+                      ("maand", "maanden"),
+                      ("dag", "dagen"),
+                      ("uur", "uur"),
+                      ("minuut", "minuten"),
+                      ("seconde", "seconden"),
+                      ("milliseconde", "milliseconden"),
+                      ("microseconde", "microseconden"),
+                      ("nanoseconde", "nanoseconden")],
+             "sv" => [("år", "år"),
+                      ("månad", "månader"),
+                      ("dag", "dagar"),
+                      ("timme", "timmar"),
+                      ("minut", "minuter"),
+                      ("sekund", "sekunder"),
+                      ("millisekund", "millisekunder"),
+                      ("mikrosekund", "mikrosekunder"),
+                      ("nanosekund", "nanosekunder")],
+             "da" => [("år", "år"),
+                      ("måned", "måneder"),
+                      ("dag", "dage"),
+                      ("time", "timer"),
+                      ("minut", "minutter"),
+                      ("sekund", "sekunder"),
+                      ("millisekund", "millisekunder"),
+                      ("mikrosekund", "mikrosekunder"),
+                      ("nanosekund", "nanosekunder")],
+             "no" | "nb" => [("år", "år"),
+                             ("måned", "måneder"),
+                             ("dag", "dager"),
+                             ("time", "timer"),
+                             ("minutt", "minutter"),
+                             ("sekund", "sekunder"),
+                             ("millisekund", "millisekunder"),
+                             ("mikrosekund", "mikrosekunder"),
+                             ("nanosekund", "nanosekunder")],
+             "nn" => [("år", "år"),
+                      ("månad", "månader"),
+                      ("dag", "dagar"),
+                      ("time", "timar"),
+                      ("minutt", "minutt"),
+                      ("sekund", "sekund"),
+                      ("millisekund", "millisekund"),
+                      ("mikrosekund", "mikrosekund"),
+                      ("nanosekund", "nanosekund")],
+             _ => return None,
+         })
+}
+
+// Unit strings used by the verbose formatters, one label for a count of exactly one and one for
+// every other count, and the spacing between a count and its label. Defaults to the English
+// unit names after a space.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Labels {
+    labels: [(String, String); 9],
+    spacing: UnitSpacing,
+}
+
+// How the parts of a multi-unit phrase are joined: "2 hours, 30 minutes and 5 seconds" is
+// `And("and")`, `OxfordAnd` adds the comma before the conjunction when there are three or more
+// parts, `Commas` and `Spaces` use no conjunction at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListJoin {
+    And(String),
+    OxfordAnd(String),
+    Commas,
+    Spaces,
+}
+
+// Mirrors Python humanize's `naturaldelta(value, months=True, minimum_unit="seconds")`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NaturalDelta {
+    months: bool,
+    minimum_unit: Unit,
+    labels: Labels,
+    zero_style: ZeroStyle<'static>,
+}
+
+// Mirrors Python humanize's `precisedelta(value, minimum_unit="seconds", suppress=(),
+// format="%0.2f")`, with `precision` standing in for the printf-style format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreciseDelta {
+    minimum_unit: Unit,
+    suppress: Vec<Unit>,
+    precision: usize,
+    labels: Labels,
+    list_join: ListJoin,
+    zero_style: ZeroStyle<'static>,
+}
+
+// Speech Synthesis Markup for voice assistants: every count is a cardinal `<say-as>` and a
+// `<break>` follows each part but the last, so "2 hours and 30 minutes" is read as "two hours,
+// and thirty minutes". Subsecond parts are dropped. A negative duration makes the first count
+// negative, which the synthesizer reads as "minus" in its own language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ssml {
+    language: Option<String>,
+    pause_ms: u32,
+    labels: Labels,
+    list_join: ListJoin,
+}
+
+// How `largest_unit_only` rounds to its unit: to the nearest whole one with ties going up (2.5
+// hours is "3 hours") or down ("2 hours"), or to the nearest half ("2.5 hours").
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TieBreak {
+    #[default]
+    Up,
+    Down,
+    Half,
+}
+
+// A compact form for the visible UI ("2h30m4s250ms") and a full-precision verbose one for
+// aria-labels and tooltips, both rendered from the same decomposition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Description {
+    pub compact: String,
+    pub long: String,
+}
+
+// Per-unit abbreviations, the first of each set being the one written out ("2 min"). `parse`
+// reads "1mo 2d 5min"-style text with every abbreviation; one listed under two units, like "m"
+// for both minutes and months, is rejected with `AmbiguousUnit`, and `ambiguities` reports them
+// up front. Abbreviations match case-insensitively when there is no exact match, so "2H 30M"
+// parses, and with `with_full_width` the full-width forms of ASCII ("２ｈ") are read as ASCII.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Abbreviations {
+    sets: [Vec<String>; 9],
+    full_width: bool,
+}
+
+pub fn natural_delta<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    NaturalDelta::new().format(time)
+}
+
+pub fn precise_delta<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    PreciseDelta::new().format(time)
+}
+
+// Voice-procedure style for TTS and radio logs: every count is read digit by digit, "two hours
+// three zero minutes". Zero units are skipped and subsecond parts are dropped.
+pub fn spoken_digits<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    spoken_digits_with(time, &Labels::new())
+}
+
+// `spoken_digits` with the unit names and spacing of `labels`.
+pub fn spoken_digits_with<D>(time: D, labels: &Labels) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let spacing = labels.spacing.as_str();
+    let spoken: Vec<String> = whole_parts(&time)
+        .iter()
+        .filter(|&&(_, value)| value > 0)
+        .map(|&(unit, value)| {
+                 format!("{}{}{}", digit_words(value), spacing, labels.plural(value as f64, unit))
+             })
+        .collect();
+    if spoken.is_empty() {
+        return Ok(format!("{}{}{}", DIGIT_WORDS[0], spacing, labels.other(Unit::Seconds)));
+    }
+    Ok(spoken.join(" "))
+}
+
+// The duration as a count of its largest unit, "3 days", for file-manager style columns,
+// from years down to seconds. The sign is dropped, as in `natural_delta`, and a rounding that
+// reaches the next unit moves up to it, so 23h50m is "1 day".
+pub fn largest_unit_only<D>(time: D, tie_break: TieBreak) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let nanos = time.total_nanoseconds().ok_or(FormatError::ValueOutOfRange)?;
+    let labels = Labels::new();
+
+    let mut i = match WHOLE_UNITS.iter().position(|&(_, size)| nanos >= size as u128) {
+        Some(i) => i,
+        None => return Ok(format!("0 {}", labels.other(Unit::Seconds))),
+    };
+    // Rounded counts are kept in halves of the unit.
+    let round = |size: u128| match tie_break {
+        TieBreak::Up => (nanos.saturating_mul(2) + size) / (2 * size) * 2,
+        TieBreak::Down => (nanos.saturating_mul(2) + size - 1) / (2 * size) * 2,
+        TieBreak::Half => (nanos.saturating_mul(4) + size) / (2 * size),
+    };
+    let mut halves = round(WHOLE_UNITS[i].1 as u128);
+    while i > 0 && halves * WHOLE_UNITS[i].1 as u128 >= 2 * WHOLE_UNITS[i - 1].1 as u128 {
+        i -= 1;
+        halves = round(WHOLE_UNITS[i].1 as u128);
+    }
+
+    let unit = WHOLE_UNITS[i].0;
+    let value = halves as f64 / 2.0;
+    Ok(format!("{} {}", value, labels.plural(value, unit)))
+}
+
+pub fn ssml<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    Ssml::new().format(time)
+}
+
+pub fn describe<D>(time: D) -> Result<Description, FormatError>
+    where D: Decompose
+{
+    describe_with(time, &Labels::new(), &ListJoin::default(), "minus ")
+}
+
+// `describe` with the long form in another language: unit names and spacing from `labels`, the
+// parts joined by `list_join` and a negative duration starting with `minus`, such as "moins ".
+// The compact form is always unspaced.
+pub fn describe_with<D>(time: D,
+                        labels: &Labels,
+                        list_join: &ListJoin,
+                        minus: &str)
+                        -> Result<Description, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let spacing = labels.spacing.as_str();
+    let mut parts: Vec<String> = time.fields()
+        .filter(|&(_, value)| value > 0)
+        .map(|(unit, value)| format!("{}{}{}", value, spacing, labels.plural(value as f64, unit)))
+        .collect();
+    let long = if parts.is_empty() {
+        format!("0{}{}", spacing, labels.other(Unit::Seconds))
+    } else {
+        if time.is_negative() {
+            parts[0].insert_str(0, minus);
+        }
+        list_join.join(&parts)
+    };
+
+    Ok(Description {
+           compact: CompactDuration::new(time).to_string(),
+           long,
+       })
+}
+
+// Phrases how `time` differs from `baseline` for benchmark reports: "12% / 1h5m slower", "3s
+// faster", "same as baseline". The percentage is relative to the baseline, rounded to a whole
+// number, and left out when the baseline is zero; the absolute part is a `CompactDuration`.
+pub fn compare<D, B>(time: D, baseline: B) -> Result<String, FormatError>
+    where D: Decompose,
+          B: Decompose
+{
+    let time = signed_nanos(time)?;
+    let baseline = signed_nanos(baseline)?;
+    let diff = time.checked_sub(baseline).ok_or(FormatError::ValueOutOfRange)?;
+    if diff == 0 {
+        return Ok("same as baseline".to_owned());
+    }
+
+    let abs = diff.unsigned_abs();
+    if abs / 1_000_000_000 > u64::MAX as u128 {
+        return Err(FormatError::ValueOutOfRange);
+    }
+    let abs_time = time::Duration::new((abs / 1_000_000_000) as u64, (abs % 1_000_000_000) as u32)
+        .decompose()
+        .map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let direction = if diff > 0 { "slower" } else { "faster" };
+
+    let absolute = CompactDuration::new(abs_time);
+    Ok(match baseline.unsigned_abs() {
+           0 => format!("{} {}", absolute, direction),
+           base => {
+               let percent = (abs.saturating_mul(100) + base / 2) / base;
+               format!("{}% / {} {}", percent, absolute, direction)
+           }
+       })
+}
+
+impl Labels {
+    pub fn new() -> Labels {
+        let names = |unit: Unit| (unit_names(unit).0.to_owned(), unit_names(unit).1.to_owned());
+        Labels {
+            labels: [names(Unit::Years),
+                     names(Unit::Months),
+                     names(Unit::Days),
+                     names(Unit::Hours),
+                     names(Unit::Minutes),
+                     names(Unit::Seconds),
+                     names(Unit::Milliseconds),
+                     names(Unit::Microseconds),
+                     names(Unit::Nanoseconds)],
+            spacing: UnitSpacing::Space,
+        }
+    }
+    // The unit names of a language `ListJoin::for_language` knows, given the same way.
+    pub fn for_language(tag: &str) -> Option<Labels> {
+        let tag = tag.to_ascii_lowercase().replace('_', "-");
+        match tag.split('-').next().unwrap_or("") {
+            "en" => Some(Labels::new()),
+            language => {
+                localized_names(language).map(|names| {
+                    let mut labels = Labels::new();
+                    for (label, &(one, other)) in labels.labels.iter_mut().zip(names.iter()) {
+                        *label = (one.to_owned(), other.to_owned());
+                    }
+                    labels
+                })
+            }
+        }
+    }
+    pub fn abbreviated() -> Labels {
+        Labels::new()
+            .with_unit(Unit::Nanoseconds, "ns", "ns")
+            .with_unit(Unit::Microseconds, "µs", "µs")
+            .with_unit(Unit::Milliseconds, "ms", "ms")
+            .with_unit(Unit::Seconds, "sec", "secs")
+            .with_unit(Unit::Minutes, "min", "mins")
+            .with_unit(Unit::Hours, "hr", "hrs")
+            .with_unit(Unit::Days, "day", "days")
+            .with_unit(Unit::Months, "mo", "mos")
+            .with_unit(Unit::Years, "yr", "yrs")
+    }
+    #[must_use]
+    pub fn with_unit(mut self, unit: Unit, one: &str, other: &str) -> Labels {
+        self.labels[unit as usize] = (one.to_owned(), other.to_owned());
+        self
+    }
+    #[must_use]
+    pub fn with_spacing(mut self, spacing: UnitSpacing) -> Labels {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn one(&self, unit: Unit) -> &str {
+        &self.labels[unit as usize].0
+    }
+    pub fn other(&self, unit: Unit) -> &str {
+        &self.labels[unit as usize].1
+    }
+    pub fn spacing(&self) -> UnitSpacing {
+        self.spacing
+    }
+
+    fn plural(&self, value: f64, unit: Unit) -> &str {
+        if value == 1.0 { self.one(unit) } else { self.other(unit) }
+    }
+
+    // Equivalent of Python's `ngettext("%d unit", "%d units", value) % value`.
+    fn count(&self, value: f64, unit: Unit) -> String {
+        format!("{:.0}{}{}", value.trunc(), self.spacing.as_str(), self.plural(value, unit))
+    }
+
+    // "an hour" with the English labels, "1 hr" once they have been customized.
+    fn single(&self, unit: Unit) -> String {
+        if self.one(unit) != unit_names(unit).0 {
+            return self.count(1.0, unit);
+        }
+        match unit {
+            Unit::Hours => "an hour".to_owned(),
+            _ => format!("a {}", self.one(unit)),
+        }
+    }
+}
+
+impl Default for Labels {
+    fn default() -> Labels {
+        Labels::new()
+    }
+}
+
+impl ListJoin {
+    // The usual conjunction for a language, given as a BCP 47 tag or its primary subtag
+    // ("de", "fr-CA"). American English gets the Oxford comma.
+    pub fn for_language(tag: &str) -> Option<ListJoin> {
+        let tag = tag.to_ascii_lowercase().replace('_', "-");
+        if tag == "en-us" {
+            return Some(ListJoin::OxfordAnd("and".to_owned()));
+        }
+        let word = match tag.split('-').next().unwrap_or("") {
+            "en" => "and",
+            "de" => "und",
+            "fr" => "et",
+            "es" => "y",
+            "it" | "pt" => "e",
+            "nl" => "en",
+            "sv" => "och",
+            "da" | "no" | "nb" | "nn" => "og",
+            _ => return None,
+        };
+        Some(ListJoin::And(word.to_owned()))
+    }
+
+    pub fn join(&self, parts: &[String]) -> String {
+        let (last, head) = match parts.split_last() {
+            Some((last, [])) => return last.clone(),
+            Some(split) => split,
+            None => return String::new(),
+        };
+        match *self {
+            ListJoin::And(ref word) => format!("{} {} {}", head.join(", "), word, last),
+            ListJoin::OxfordAnd(ref word) if head.len() > 1 => {
+                format!("{}, {} {}", head.join(", "), word, last)
+            }
+            ListJoin::OxfordAnd(ref word) => format!("{} {} {}", head[0], word, last),
+            ListJoin::Commas => parts.join(", "),
+            ListJoin::Spaces => parts.join(" "),
+        }
+    }
+}
+
+impl Default for ListJoin {
+    fn default() -> ListJoin {
+        ListJoin::And("and".to_owned())
+    }
+}
+
+impl NaturalDelta {
+    pub fn new() -> NaturalDelta {
+        NaturalDelta {
+            months: true,
+            minimum_unit: Unit::Seconds,
+            labels: Labels::new(),
+            zero_style: ZeroStyle::Normal,
+        }
+    }
+    #[must_use]
+    pub fn with_months(mut self, months: bool) -> NaturalDelta {
+        self.months = months;
+        self
+    }
+    #[must_use]
+    pub fn with_minimum_unit(mut self, unit: Unit) -> NaturalDelta {
+        assert!(unit <= Unit::Seconds, "minimum unit not supported");
+        self.minimum_unit = unit;
+        self
+    }
+    #[must_use]
+    pub fn with_labels(mut self, labels: Labels) -> NaturalDelta {
+        self.labels = labels;
+        self
+    }
+    #[must_use]
+    pub fn with_zero_style(mut self, zero_style: ZeroStyle<'static>) -> NaturalDelta {
+        self.zero_style = zero_style;
+        self
+    }
+
+    pub fn format<D>(&self, time: D) -> Result<String, FormatError>
+        where D: Decompose
+    {
+        let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+        if let Some(text) = self.zero_style.replacement(&time) {
+            return Ok(text.to_owned());
+        }
+        let (total_days, seconds, microseconds) = python_parts(&time);
+        let years = (total_days / DAYS_PER_YEAR).floor();
+        let days = total_days % DAYS_PER_YEAR;
+        let num_months = (days / DAYS_PER_MONTH).floor();
+
+        let labels = &self.labels;
+        let s = if years == 0.0 && days < 1.0 {
+            if seconds == 0.0 {
+                if self.minimum_unit == Unit::Microseconds && microseconds < 1000.0 {
+                    labels.count(microseconds, Unit::Microseconds)
+                } else if self.minimum_unit == Unit::Milliseconds ||
+                          (self.minimum_unit == Unit::Microseconds && microseconds >= 1000.0) {
+                    labels.count(microseconds / 1000.0, Unit::Milliseconds)
+                } else {
+                    "a moment".to_owned()
+                }
+            } else if seconds == 1.0 {
+                labels.single(Unit::Seconds)
+            } else if seconds < 60.0 {
+                labels.count(seconds, Unit::Seconds)
+            } else if seconds < 120.0 {
+                labels.single(Unit::Minutes)
+            } else if seconds < 3600.0 {
+                labels.count((seconds / 60.0).floor(), Unit::Minutes)
+            } else if seconds < 7200.0 {
+                labels.single(Unit::Hours)
+            } else {
+                labels.count((seconds / 3600.0).floor(), Unit::Hours)
+            }
+        } else if years == 0.0 {
+            if days == 1.0 {
+                labels.single(Unit::Days)
+            } else if !self.months || num_months == 0.0 {
+                labels.count(days, Unit::Days)
+            } else if num_months == 1.0 {
+                labels.single(Unit::Months)
+            } else {
+                labels.count(num_months, Unit::Months)
+            }
+        } else if years == 1.0 {
+            let one_year = labels.count(1.0, Unit::Years);
+            if num_months == 0.0 && days == 0.0 {
+                labels.single(Unit::Years)
+            } else if num_months == 0.0 || !self.months {
+                format!("{}, {}", one_year, labels.count(days, Unit::Days))
+            } else {
+                format!("{}, {}", one_year, labels.count(num_months, Unit::Months))
+            }
+        } else {
+            format!("{}{}{}",
+                    group_thousands(years),
+                    labels.spacing.as_str(),
+                    labels.plural(years, Unit::Years))
+        };
+        Ok(s)
+    }
+}
+
+impl Default for NaturalDelta {
+    fn default() -> NaturalDelta {
+        NaturalDelta::new()
+    }
+}
+
+impl PreciseDelta {
+    pub fn new() -> PreciseDelta {
+        PreciseDelta {
+            minimum_unit: Unit::Seconds,
+            suppress: Vec::new(),
+            precision: 2,
+            labels: Labels::new(),
+            list_join: ListJoin::default(),
+            zero_style: ZeroStyle::Normal,
+        }
+    }
+    #[must_use]
+    pub fn with_minimum_unit(mut self, unit: Unit) -> PreciseDelta {
+        self.minimum_unit = unit;
+        self.check_suitable_minimum_unit();
+        self
+    }
+    #[must_use]
+    pub fn with_suppressed(mut self, units: &[Unit]) -> PreciseDelta {
+        self.suppress = units.to_vec();
+        self.check_suitable_minimum_unit();
+        self
+    }
+    #[must_use]
+    pub fn with_precision(mut self, precision: usize) -> PreciseDelta {
+        self.precision = precision;
+        self
+    }
+    #[must_use]
+    pub fn with_labels(mut self, labels: Labels) -> PreciseDelta {
+        self.labels = labels;
+        self
+    }
+    #[must_use]
+    pub fn with_list_join(mut self, list_join: ListJoin) -> PreciseDelta {
+        self.list_join = list_join;
+        self
+    }
+    #[must_use]
+    pub fn with_zero_style(mut self, zero_style: ZeroStyle<'static>) -> PreciseDelta {
+        self.zero_style = zero_style;
+        self
+    }
+
+    fn check_suitable_minimum_unit(&self) {
+        assert!(self.suitable_minimum_unit().is_some(),
+                "minimum unit is suppressed and no suitable replacement was found");
+    }
+
+    fn suitable_minimum_unit(&self) -> Option<Unit> {
+        UNITS.iter()
+            .cloned()
+            .filter(|unit| *unit >= self.minimum_unit)
+            .find(|unit| !self.suppress.contains(unit))
+    }
+
+    pub fn format<D>(&self, time: D) -> Result<String, FormatError>
+        where D: Decompose
+    {
+        let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+        if let Some(text) = self.zero_style.replacement(&time) {
+            return Ok(text.to_owned());
+        }
+        let min_unit = self.suitable_minimum_unit().expect("checked when configured");
+        let is_suppressed = |unit: Unit| unit < min_unit || self.suppress.contains(&unit);
+        let quotient_and_remainder = |value: f64, divisor: f64, unit: Unit| if unit == min_unit {
+            (value / divisor, 0.0)
+        } else if is_suppressed(unit) {
+            (0.0, value)
+        } else {
+            let rem = value % divisor;
+            ((value - rem) / divisor, rem)
+        };
+
+        let (days, secs, usecs) = python_parts(&time);
+        let (years, days) = quotient_and_remainder(days, DAYS_PER_YEAR, Unit::Years);
+        let (months, days) = quotient_and_remainder(days, DAYS_PER_MONTH, Unit::Months);
+        let secs = days * units::SECS_PER_DAY as f64 + secs;
+        let (days, secs) = quotient_and_remainder(secs, units::SECS_PER_DAY as f64, Unit::Days);
+        let (hours, secs) = quotient_and_remainder(secs, 3600.0, Unit::Hours);
+        let (minutes, secs) = quotient_and_remainder(secs, 60.0, Unit::Minutes);
+        let usecs = secs * 1.0e6 + usecs;
+        let (secs, usecs) = quotient_and_remainder(usecs, 1.0e6, Unit::Seconds);
+        let (msecs, usecs) = quotient_and_remainder(usecs, 1000.0, Unit::Milliseconds);
+
+        let values = [usecs, msecs, secs, minutes, hours, days, months, years];
+        let mut texts = Vec::new();
+        for (&unit, &value) in UNITS.iter().zip(values.iter()).rev() {
+            if value > 0.0 || (texts.is_empty() && unit == min_unit) {
+                let spacing = self.labels.spacing.as_str();
+                let label = self.labels.plural(value, unit);
+                let text = if unit == min_unit && value.fract() > 0.0 {
+                    format!("{:.*}{}{}", self.precision, value, spacing, label)
+                } else if unit == Unit::Years {
+                    format!("{}{}{}", group_thousands(value), spacing, label)
+                } else {
+                    self.labels.count(value, unit)
+                };
+                texts.push(text);
+            }
+            if unit == min_unit {
+                break;
+            }
+        }
+
+        Ok(self.list_join.join(&texts))
+    }
+}
+
+impl Default for PreciseDelta {
+    fn default() -> PreciseDelta {
+        PreciseDelta::new()
+    }
+}
+
+impl Ssml {
+    pub fn new() -> Ssml {
+        Ssml {
+            language: None,
+            pause_ms: 200,
+            labels: Labels::new(),
+            list_join: ListJoin::default(),
+        }
+    }
+    // Sets `xml:lang` on the `<speak>` element and, for languages `ListJoin::for_language`
+    // knows, the conjunction and unit labels, replacing any set before.
+    #[must_use]
+    pub fn with_language(mut self, tag: &str) -> Ssml {
+        if let Some(list_join) = ListJoin::for_language(tag) {
+            self.list_join = list_join;
+        }
+        if let Some(labels) = Labels::for_language(tag) {
+            self.labels = labels;
+        }
+        self.language = Some(tag.to_owned());
+        self
+    }
+    #[must_use]
+    pub fn with_pause_ms(mut self, pause_ms: u32) -> Ssml {
+        self.pause_ms = pause_ms;
+        self
+    }
+    #[must_use]
+    pub fn with_labels(mut self, labels: Labels) -> Ssml {
+        self.labels = labels;
+        self
+    }
+    #[must_use]
+    pub fn with_list_join(mut self, list_join: ListJoin) -> Ssml {
+        self.list_join = list_join;
+        self
+    }
+
+    pub fn format<D>(&self, time: D) -> Result<String, FormatError>
+        where D: Decompose
+    {
+        let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+        let mut parts: Vec<(Unit, u64)> =
+            whole_parts(&time).iter().cloned().filter(|&(_, value)| value > 0).collect();
+        let sign = if time.is_negative() && !parts.is_empty() { "-" } else { "" };
+        if parts.is_empty() {
+            parts.push((Unit::Seconds, 0));
+        }
+
+        let last = parts.len() - 1;
+        let said: Vec<String> = parts.iter()
+            .enumerate()
+            .map(|(i, &(unit, value))| {
+                let label = escape_xml(self.labels.plural(value as f64, unit));
+                let pause = if i < last {
+                    format!("<break time=\"{}ms\"/>", self.pause_ms)
+                } else {
+                    String::new()
+                };
+                let sign = if i == 0 { sign } else { "" };
+                format!("<say-as interpret-as=\"cardinal\">{}{}</say-as>{}{}{}",
+                        sign,
+                        value,
+                        self.labels.spacing.as_str(),
+                        label,
+                        pause)
+            })
+            .collect();
+
+        let speak = match self.language {
+            Some(ref language) => format!("<speak xml:lang=\"{}\">", escape_xml(language)),
+            None => "<speak>".to_owned(),
+        };
+        Ok(format!("{}{}</speak>", speak, self.list_join.join(&said)))
+    }
+}
+
+impl Default for Ssml {
+    fn default() -> Ssml {
+        Ssml::new()
+    }
+}
+
+impl Abbreviations {
+    pub fn new() -> Abbreviations {
+        let set = |abbreviations: &[&str]| abbreviations.iter().map(|&a| a.to_owned()).collect();
+        Abbreviations {
+            sets: [set(&["y", "yr", "yrs"]),
+                   set(&["mo", "mos"]),
+                   set(&["d", "day", "days"]),
+                   set(&["h", "hr", "hrs"]),
+                   set(&["min", "m", "mins"]),
+                   set(&["s", "sec", "secs"]),
+                   set(&["ms"]),
+                   set(&["µs", "us"]),
+                   set(&["ns"])],
+            full_width: false,
+        }
+    }
+    #[must_use]
+    pub fn with_unit(mut self, unit: Unit, abbreviations: &[&str]) -> Abbreviations {
+        assert!(!abbreviations.is_empty(), "a unit needs at least one abbreviation");
+        self.sets[unit as usize] = abbreviations.iter().map(|&a| a.to_owned()).collect();
+        self
+    }
+    #[must_use]
+    pub fn with_full_width(mut self, full_width: bool) -> Abbreviations {
+        self.full_width = full_width;
+        self
+    }
+
+    pub fn abbreviations(&self, unit: Unit) -> &[String] {
+        &self.sets[unit as usize]
+    }
+    // The abbreviation written for `unit`.
+    pub fn abbreviation(&self, unit: Unit) -> &str {
+        &self.sets[unit as usize][0]
+    }
+    // Labels writing every unit as its abbreviation, for `NaturalDelta` and `PreciseDelta`.
+    pub fn labels(&self) -> Labels {
+        ABBREVIATED_UNITS.iter().fold(Labels::new(), |labels, &unit| {
+            let abbreviation = self.abbreviation(unit);
+            labels.with_unit(unit, abbreviation, abbreviation)
+        })
+    }
+
+    // Every abbreviation listed under more than one unit, with those units.
+    pub fn ambiguities(&self) -> Vec<(String, Vec<Unit>)> {
+        let mut ambiguities: Vec<(String, Vec<Unit>)> = Vec::new();
+        for abbreviation in self.sets.iter().flat_map(|set| set.iter()) {
+            if ambiguities.iter().any(|(seen, _)| seen == abbreviation) {
+                continue;
+            }
+            let units = self.units(abbreviation);
+            if units.len() > 1 {
+                ambiguities.push((abbreviation.clone(), units));
+            }
+        }
+        ambiguities
+    }
+
+    // The unit `abbreviation` stands for: `UnknownField` if it is in no set and `AmbiguousUnit`
+    // if it is in several, exactly or once case is ignored.
+    pub fn unit(&self, abbreviation: &str) -> Result<Unit, FormatError> {
+        let mut units = self.units(abbreviation);
+        if units.is_empty() {
+            let folded = abbreviation.to_lowercase();
+            units = ABBREVIATED_UNITS.iter()
+                .cloned()
+                .filter(|&unit| self.abbreviations(unit).iter().any(|a| a.to_lowercase() == folded))
+                .collect();
+        }
+        match *units {
+            [] => Err(FormatError::UnknownField),
+            [unit] => Ok(unit),
+            _ => Err(FormatError::AmbiguousUnit),
+        }
+    }
+
+    // Reads counts followed by abbreviations, optionally separated by whitespace or commas, as
+    // in "2h 30min" or "1.5 d". Months are 30.5 days and years 365, as in `NaturalDelta`. Any
+    // Unicode whitespace, no-break spaces included, separates.
+    pub fn parse(&self, text: &str) -> Result<time::Duration, FormatError> {
+        let text = if self.full_width {
+            text.chars().map(from_full_width).collect()
+        } else {
+            text.to_owned()
+        };
+        let mut total: u128 = 0;
+        let mut rest = text.trim();
+        if rest.is_empty() {
+            return Err(FormatError::UnknownField);
+        }
+        while !rest.is_empty() {
+            let number_len = rest.find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+                .unwrap_or(rest.len());
+            let (number, after) = rest.split_at(number_len);
+            let after = after.trim_start();
+            let unit_len = after.find(|ch: char| !ch.is_alphabetic()).unwrap_or(after.len());
+            let (abbreviation, after) = after.split_at(unit_len);
+            if number.is_empty() || abbreviation.is_empty() {
+                return Err(FormatError::UnknownField);
+            }
+
+            let scale = self.unit(abbreviation)?.nanoseconds();
+            let value = scaled_decimal(number, scale).ok_or(FormatError::ValueOutOfRange)?;
+            total = total.checked_add(value).ok_or(FormatError::ValueOutOfRange)?;
+            rest = after.trim_start_matches(|ch: char| ch.is_whitespace() || ch == ',');
+        }
+
+        let secs = total / units::NANOS_PER_SEC as u128;
+        if secs > u64::MAX as u128 {
+            return Err(FormatError::ValueOutOfRange);
+        }
+        Ok(time::Duration::new(secs as u64, (total % units::NANOS_PER_SEC as u128) as u32))
+    }
+
+    fn units(&self, abbreviation: &str) -> Vec<Unit> {
+        ABBREVIATED_UNITS.iter()
+            .cloned()
+            .filter(|&unit| self.abbreviations(unit).iter().any(|a| a == abbreviation))
+            .collect()
+    }
+}
+
+impl Default for Abbreviations {
+    fn default() -> Abbreviations {
+        Abbreviations::new()
+    }
+}
+
+// Python's timedelta normalizes to (days, seconds < 86400, microseconds < 10^6) and humanize
+// works on its absolute value.
+fn python_parts(time: &DecomposedTime) -> (f64, f64, f64) {
+    let days = time.total_days() as f64;
+    let seconds = time.hours() as f64 * 3600.0 + time.minutes() as f64 * 60.0 +
+                  time.seconds() as f64;
+    let microseconds = time.milliseconds() as f64 * 1000.0 + time.microseconds() as f64;
+    (days, seconds, microseconds)
+}
+
+fn whole_parts(time: &DecomposedTime) -> [(Unit, u64); 5] {
+    [(Unit::Years, time.years()),
+     (Unit::Days, time.days() as u64),
+     (Unit::Hours, time.hours() as u64),
+     (Unit::Minutes, time.minutes() as u64),
+     (Unit::Seconds, time.seconds() as u64)]
+}
+
+fn signed_nanos<D>(time: D) -> Result<i128, FormatError>
+    where D: Decompose
+{
+    let time = time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+    let nanos = time.total_nanoseconds()
+        .filter(|&nanos| nanos <= i128::MAX as u128)
+        .ok_or(FormatError::ValueOutOfRange)? as i128;
+    Ok(if time.is_negative() { -nanos } else { nanos })
+}
+
+// Full-width ASCII (U+FF01 to U+FF5E) as the ASCII character it stands for.
+fn from_full_width(ch: char) -> char {
+    match ch {
+        '\u{ff01}'..='\u{ff5e}' => ::std::char::from_u32(ch as u32 - 0xfee0).unwrap_or(ch),
+        _ => ch,
+    }
+}
+
+// `number` ("12", "1.5", ".25") times `scale`, truncated to a whole number.
+pub(crate) fn scaled_decimal(number: &str, scale: u64) -> Option<u128> {
+    let (whole, fraction) = match number.find('.') {
+        Some(point) => (&number[..point], &number[point + 1..]),
+        None => (number, ""),
+    };
+    if (whole.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+        return None;
+    }
+    let whole: u128 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+    let value = whole.checked_mul(scale as u128)?;
+    let mut denominator: u128 = 1;
+    let mut numerator: u128 = 0;
+    // Nanosecond scales have at most 20 digits, so more decimals than that can't matter.
+    for digit in fraction.bytes().take(20) {
+        numerator = numerator * 10 + (digit - b'0') as u128;
+        denominator *= 10;
+    }
+    value.checked_add(numerator * scale as u128 / denominator)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn digit_words(value: u64) -> String {
+    let words: Vec<&str> = value.to_string()
+        .bytes()
+        .map(|digit| DIGIT_WORDS[(digit - b'0') as usize])
+        .collect();
+    words.join(" ")
+}
+
+fn group_thousands(value: f64) -> String {
+    let digits = format!("{:.0}", value.trunc());
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(ch);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_natural_delta() {
+        assert_eq!(natural_delta(Duration::from_secs(1001)).unwrap(),
+                   "16 minutes");
+        assert_eq!(natural_delta(Duration::from_millis(500)).unwrap(), "a moment");
+        assert_eq!(natural_delta(Duration::from_secs(1)).unwrap(), "a second");
+        assert_eq!(natural_delta(Duration::from_secs(90)).unwrap(), "a minute");
+        assert_eq!(natural_delta(Duration::from_secs(3600)).unwrap(), "an hour");
+        assert_eq!(natural_delta(Duration::from_secs(23 * 3600)).unwrap(), "23 hours");
+        assert_eq!(natural_delta(Duration::from_secs(86_400)).unwrap(), "a day");
+        assert_eq!(natural_delta(Duration::from_secs(7 * 86_400)).unwrap(), "7 days");
+        assert_eq!(natural_delta(Duration::from_secs(31 * 86_400)).unwrap(), "a month");
+        assert_eq!(natural_delta(Duration::from_secs(400 * 86_400)).unwrap(),
+                   "1 year, 1 month");
+        assert_eq!(NaturalDelta::new()
+                       .with_months(false)
+                       .format(Duration::from_secs(400 * 86_400))
+                       .unwrap(),
+                   "1 year, 35 days");
+        assert_eq!(natural_delta(Duration::from_secs(1200 * 31_536_000)).unwrap(),
+                   "1,200 years");
+        let negative = DecomposedTime::from_secs_nanos(3 * 3600, 0, false);
+        assert_eq!(natural_delta(negative).unwrap(), "3 hours");
+
+        let micros = DecomposedTime::default().with_fractional_seconds(0.000004);
+        assert_eq!(NaturalDelta::new()
+                       .with_minimum_unit(Unit::Microseconds)
+                       .format(micros.clone())
+                       .unwrap(),
+                   "4 microseconds");
+
+        let below = ZeroStyle::Below(time::Duration::from_millis(1), "<1ms");
+        let natural = NaturalDelta::new().with_zero_style(below);
+        assert_eq!(natural.format(micros).unwrap(), "<1ms");
+        assert_eq!(natural.format(Duration::from_secs(2)).unwrap(), "2 seconds");
+    }
+
+    #[test]
+    fn test_precise_delta() {
+        let delta = DecomposedTime::default()
+            .with_days(2)
+            .with_hours(1)
+            .with_seconds(33)
+            .with_fractional_seconds(0.123);
+
+        assert_eq!(precise_delta(delta.clone()).unwrap(),
+                   "2 days, 1 hour and 33.12 seconds");
+        assert_eq!(PreciseDelta::new()
+                       .with_minimum_unit(Unit::Microseconds)
+                       .format(delta.clone())
+                       .unwrap(),
+                   "2 days, 1 hour, 33 seconds and 123 milliseconds");
+        assert_eq!(PreciseDelta::new()
+                       .with_suppressed(&[Unit::Days])
+                       .with_precision(4)
+                       .format(delta)
+                       .unwrap(),
+                   "49 hours and 33.1230 seconds");
+        assert_eq!(PreciseDelta::new()
+                       .with_minimum_unit(Unit::Minutes)
+                       .format(Duration::from_secs(1))
+                       .unwrap(),
+                   "0.02 minutes");
+        assert_eq!(precise_delta(Duration::from_secs(0)).unwrap(), "0 seconds");
+        assert_eq!(PreciseDelta::new()
+                       .with_zero_style(ZeroStyle::Zero("instant"))
+                       .format(Duration::from_secs(0))
+                       .unwrap(),
+                   "instant");
+    }
+
+    #[test]
+    fn test_list_join() {
+        let time = Duration::from_secs(9005);
+        let format = |list_join| {
+            PreciseDelta::new()
+                .with_list_join(list_join)
+                .format(time)
+                .unwrap()
+        };
+        assert_eq!(format(ListJoin::default()), "2 hours, 30 minutes and 5 seconds");
+        assert_eq!(format(ListJoin::OxfordAnd("and".to_owned())),
+                   "2 hours, 30 minutes, and 5 seconds");
+        assert_eq!(format(ListJoin::Commas), "2 hours, 30 minutes, 5 seconds");
+        assert_eq!(format(ListJoin::Spaces), "2 hours 30 minutes 5 seconds");
+        assert_eq!(format(ListJoin::for_language("de-AT").unwrap()),
+                   "2 hours, 30 minutes und 5 seconds");
+
+        let pair = ["a".to_owned(), "b".to_owned()];
+        assert_eq!(ListJoin::OxfordAnd("and".to_owned()).join(&pair), "a and b");
+        assert_eq!(ListJoin::for_language("fr").unwrap().join(&pair), "a et b");
+        assert_eq!(ListJoin::for_language("en_US"),
+                   Some(ListJoin::OxfordAnd("and".to_owned())));
+        assert_eq!(ListJoin::for_language("tlh"), None);
+        assert_eq!(Labels::for_language("es_MX").unwrap().other(Unit::Days), "días");
+        assert_eq!(Labels::for_language("tlh"), None);
+    }
+
+    #[test]
+    fn test_spoken_digits() {
+        assert_eq!(spoken_digits(Duration::from_secs(9000)).unwrap(),
+                   "two hours three zero minutes");
+        assert_eq!(spoken_digits(Duration::from_millis(86_505_500)).unwrap(),
+                   "one day one minute four five seconds");
+        assert_eq!(spoken_digits(Duration::from_millis(250)).unwrap(), "zero seconds");
+        let narrow = Labels::new().with_spacing(UnitSpacing::NarrowNoBreak);
+        assert_eq!(spoken_digits_with(Duration::from_secs(120), &narrow).unwrap(),
+                   "two\u{202f}minutes");
+    }
+
+    #[test]
+    fn test_ssml() {
+        let time = Duration::from_secs(9000);
+        assert_eq!(ssml(time).unwrap(),
+                   concat!("<speak><say-as interpret-as=\"cardinal\">2</say-as> hours",
+                           "<break time=\"200ms\"/> and ",
+                           "<say-as interpret-as=\"cardinal\">30</say-as> minutes</speak>"));
+
+        assert_eq!(Ssml::new()
+                       .with_language("de-DE")
+                       .with_pause_ms(100)
+                       .format(Duration::from_secs(61))
+                       .unwrap(),
+                   concat!("<speak xml:lang=\"de-DE\">",
+                           "<say-as interpret-as=\"cardinal\">1</say-as> Minute",
+                           "<break time=\"100ms\"/> und ",
+                           "<say-as interpret-as=\"cardinal\">1</say-as> Sekunde</speak>"));
+        let negative = DecomposedTime::from_secs_nanos(9000, 0, false);
+        assert_eq!(Ssml::new().with_language("fr-CA").format(negative).unwrap(),
+                   concat!("<speak xml:lang=\"fr-CA\">",
+                           "<say-as interpret-as=\"cardinal\">-2</say-as> heures",
+                           "<break time=\"200ms\"/> et ",
+                           "<say-as interpret-as=\"cardinal\">30</say-as> minutes</speak>"));
+        assert_eq!(ssml(DecomposedTime::from_secs_nanos(0, 500_000_000, false)).unwrap(),
+                   "<speak><say-as interpret-as=\"cardinal\">0</say-as> seconds</speak>");
+
+        assert_eq!(Ssml::new()
+                       .with_labels(Labels::new().with_unit(Unit::Seconds, "s", "s<>"))
+                       .format(Duration::from_secs(0))
+                       .unwrap(),
+                   "<speak><say-as interpret-as=\"cardinal\">0</say-as> s&lt;&gt;</speak>");
+        assert_eq!(Ssml::new()
+                       .with_labels(Labels::abbreviated().with_spacing(UnitSpacing::None))
+                       .format(Duration::from_secs(3600))
+                       .unwrap(),
+                   "<speak><say-as interpret-as=\"cardinal\">1</say-as>hr</speak>");
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = Duration::from_secs(9 * 3600);
+        let slower = Duration::from_secs(36_300);
+        assert_eq!(compare(slower, baseline).unwrap(), "12% / 1h5m slower");
+        assert_eq!(compare(Duration::from_secs(7), Duration::from_secs(10)).unwrap(),
+                   "30% / 3s faster");
+        assert_eq!(compare(Duration::from_secs(3), Duration::from_secs(0)).unwrap(),
+                   "3s slower");
+        assert_eq!(compare(baseline, baseline).unwrap(), "same as baseline");
+        let negative = DecomposedTime::from_secs_nanos(1, 0, false);
+        assert_eq!(compare(negative, Duration::from_secs(1)).unwrap(),
+                   "200% / 2s faster");
+    }
+
+    #[test]
+    fn test_largest_unit_only() {
+        let terse = |time, tie_break| largest_unit_only(time, tie_break).unwrap();
+        let hours = Duration::from_secs(9000);
+        assert_eq!(terse(hours, TieBreak::Up), "3 hours");
+        assert_eq!(terse(hours, TieBreak::Down), "2 hours");
+        assert_eq!(terse(hours, TieBreak::Half), "2.5 hours");
+        assert_eq!(terse(Duration::from_secs(9360), TieBreak::Down), "3 hours");
+        assert_eq!(terse(Duration::from_secs(276_480), TieBreak::Up), "3 days");
+        assert_eq!(terse(Duration::from_secs(60), TieBreak::Up), "1 minute");
+        assert_eq!(terse(Duration::from_secs(85_680), TieBreak::Up), "1 day");
+        assert_eq!(terse(Duration::from_secs(31_527_360), TieBreak::Half), "1 year");
+        let negative = DecomposedTime::from_secs_nanos(45, 0, false);
+        assert_eq!(largest_unit_only(negative, TieBreak::Up).unwrap(), "45 seconds");
+        assert_eq!(terse(Duration::from_millis(500), TieBreak::Up), "0 seconds");
+    }
+
+    #[test]
+    fn test_describe() {
+        let time = Duration::from_millis(7_264_250);
+        assert_eq!(describe(time).unwrap(),
+                   Description {
+                       compact: "2h1m4s250ms".to_owned(),
+                       long: "2 hours, 1 minute, 4 seconds and 250 milliseconds".to_owned(),
+                   });
+
+        let negative = describe(DecomposedTime::from_secs_nanos(86_400, 0, false)).unwrap();
+        assert_eq!(negative.compact, "-24h");
+        assert_eq!(negative.long, "minus 1 day");
+        assert_eq!(describe(Duration::from_secs(0)).unwrap().long, "0 seconds");
+        let abbreviated = Labels::abbreviated().with_spacing(UnitSpacing::None);
+        assert_eq!(describe_with(time, &abbreviated, &ListJoin::default(), "minus ")
+                       .unwrap()
+                       .long,
+                   "2hrs, 1min, 4secs and 250ms");
+    }
+
+    #[test]
+    fn test_describe_localized() {
+        let time = DecomposedTime::new(0, 0, 2, 5, 3, 0.0, false);
+        let localized = |tag, minus| {
+            let labels = Labels::for_language(tag).unwrap();
+            let list_join = ListJoin::for_language(tag).unwrap();
+            describe_with(time.clone(), &labels, &list_join, minus).unwrap().long
+        };
+        assert_eq!(localized("de", "minus "), "minus 2 Stunden, 5 Minuten und 3 Sekunden");
+        assert_eq!(localized("fr-CA", "moins "), "moins 2 heures, 5 minutes et 3 secondes");
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        let abbreviations = Abbreviations::new();
+        assert_eq!(abbreviations.unit("m"), Ok(Unit::Minutes));
+        assert_eq!(abbreviations.unit("mo"), Ok(Unit::Months));
+        assert_eq!(abbreviations.unit("fortnight"), Err(FormatError::UnknownField));
+        assert_eq!(abbreviations.ambiguities(), vec![]);
+        assert_eq!(abbreviations.parse("2h 30min").unwrap(), time::Duration::from_secs(9000));
+        assert_eq!(abbreviations.parse("1.5 d, 250ms").unwrap(),
+                   time::Duration::from_millis(129_600_250));
+        assert_eq!(abbreviations.parse("1mo").unwrap(), time::Duration::from_secs(2_635_200));
+        assert_eq!(abbreviations.parse("5 weeks"), Err(FormatError::UnknownField));
+        assert_eq!(abbreviations.parse("h"), Err(FormatError::UnknownField));
+        assert_eq!(abbreviations.parse("+5s"), Err(FormatError::UnknownField));
+        assert_eq!(PreciseDelta::new()
+                       .with_labels(abbreviations.labels())
+                       .format(Duration::from_secs(9000))
+                       .unwrap(),
+                   "2 h and 30 min");
+
+        let ambiguous = abbreviations.with_unit(Unit::Months, &["mo", "m"]);
+        assert_eq!(ambiguous.ambiguities(),
+                   vec![("m".to_owned(), vec![Unit::Minutes, Unit::Months])]);
+        assert_eq!(ambiguous.parse("5m"), Err(FormatError::AmbiguousUnit));
+        assert_eq!(ambiguous.parse("5mo").unwrap(), time::Duration::from_secs(13_176_000));
+    }
+
+    #[test]
+    fn test_abbreviations_normalization() {
+        let abbreviations = Abbreviations::new();
+        assert_eq!(abbreviations.parse("2H 30M").unwrap(), time::Duration::from_secs(9000));
+        assert_eq!(abbreviations.parse("2\u{a0}h\u{202f}30\u{3000}min").unwrap(),
+                   time::Duration::from_secs(9000));
+        assert_eq!(abbreviations.parse("２ｈ"), Err(FormatError::UnknownField));
+        assert_eq!(abbreviations.with_full_width(true).parse("２ｈ　３０ｍｉｎ").unwrap(),
+                   time::Duration::from_secs(9000));
+
+        let cased = Abbreviations::new()
+            .with_unit(Unit::Minutes, &["m"])
+            .with_unit(Unit::Months, &["M"]);
+        assert_eq!(cased.parse("1M 5m").unwrap(), time::Duration::from_secs(2_635_500));
+        assert_eq!(cased.with_unit(Unit::Minutes, &["min"]).unit("m"), Ok(Unit::Months));
+    }
+
+    #[test]
+    fn test_labels() {
+        let labels = Labels::new().with_unit(Unit::Hours, "h", "h");
+        assert_eq!(NaturalDelta::new()
+                       .with_labels(labels.clone())
+                       .format(Duration::from_secs(3600))
+                       .unwrap(),
+                   "1 h");
+        assert_eq!(NaturalDelta::new()
+                       .with_labels(labels)
+                       .format(Duration::from_secs(90))
+                       .unwrap(),
+                   "a minute");
+
+        let time = Duration::from_millis(7_265_500);
+        assert_eq!(PreciseDelta::new()
+                       .with_labels(Labels::abbreviated())
+                       .format(time)
+                       .unwrap(),
+                   "2 hrs, 1 min and 5.50 secs");
+        assert_eq!(NaturalDelta::new()
+                       .with_labels(Labels::abbreviated())
+                       .format(Duration::from_secs(400 * 86_400))
+                       .unwrap(),
+                   "1 yr, 1 mo");
+
+        let unspaced = Labels::abbreviated()
+            .with_unit(Unit::Hours, "h", "h")
+            .with_unit(Unit::Minutes, "m", "m")
+            .with_spacing(UnitSpacing::None);
+        assert_eq!(PreciseDelta::new()
+                       .with_labels(unspaced.clone())
+                       .format(Duration::from_secs(9000))
+                       .unwrap(),
+                   "2h and 30m");
+        assert_eq!(NaturalDelta::new()
+                       .with_labels(unspaced.with_spacing(UnitSpacing::NarrowNoBreak))
+                       .format(Duration::from_secs(3 * 3600))
+                       .unwrap(),
+                   "3\u{202f}h");
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+use std::time::Duration;
+
+use decomposed::{Decompose, DecomposedTime};
+use fmt::{FormatError, LeadingZeros};
+use units::Unit;
+
+// Work/rest interval training or Pomodoro: `cycles` repetitions of a work block followed by a
+// rest block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IntervalScheme {
+    work: Duration,
+    rest: Duration,
+    cycles: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Block {
+    Work,
+    Rest,
+}
+
+// Where an elapsed time falls in an `IntervalScheme`: "cycle 3 of 8, 12:30 into work block".
+// Cycles count from one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntervalPosition {
+    cycle: u32,
+    cycles: u32,
+    block: Block,
+    into_block: DecomposedTime,
+    remaining: DecomposedTime,
+}
+
+impl IntervalScheme {
+    pub fn new(work: Duration, rest: Duration, cycles: u32) -> IntervalScheme {
+        assert!(work > Duration::from_secs(0), "work block must be non-zero");
+        assert!(cycles > 0, "interval scheme needs at least one cycle");
+        IntervalScheme { work, rest, cycles }
+    }
+    // Four 25 minute work blocks with 5 minute breaks.
+    pub fn pomodoro() -> IntervalScheme {
+        IntervalScheme::new(Duration::from_secs(25 * 60), Duration::from_secs(5 * 60), 4)
+    }
+
+    pub fn work(&self) -> Duration {
+        self.work
+    }
+    pub fn rest(&self) -> Duration {
+        self.rest
+    }
+    pub fn cycles(&self) -> u32 {
+        self.cycles
+    }
+    // `None` if the whole scheme is longer than a `Duration` holds.
+    pub fn total(&self) -> Option<Duration> {
+        self.work.checked_add(self.rest).and_then(|cycle| cycle.checked_mul(self.cycles))
+    }
+
+    // `None` once every cycle is over. Negative times are `ValueOutOfRange`.
+    pub fn position<D>(&self, elapsed: D) -> Result<Option<IntervalPosition>, FormatError>
+        where D: Decompose
+    {
+        let elapsed = elapsed.decompose().map_err(|e| FormatError::DecomposeError(e.into()))?;
+        let nanos = elapsed.total_nanoseconds().ok_or(FormatError::ValueOutOfRange)?;
+        if elapsed.is_negative() && nanos > 0 {
+            return Err(FormatError::ValueOutOfRange);
+        }
+
+        let (work, rest) = (self.work.as_nanos(), self.rest.as_nanos());
+        let cycle = nanos / (work + rest);
+        if cycle >= self.cycles as u128 {
+            return Ok(None);
+        }
+        let offset = nanos % (work + rest);
+        let (block, into_block, length) = if offset < work {
+            (Block::Work, offset, work)
+        } else {
+            (Block::Rest, offset - work, rest)
+        };
+
+        Ok(Some(IntervalPosition {
+                    cycle: cycle as u32 + 1,
+                    cycles: self.cycles,
+                    block,
+                    into_block: decompose_nanos(into_block),
+                    remaining: decompose_nanos(length - into_block),
+                }))
+    }
+}
+
+impl IntervalPosition {
+    pub fn cycle(&self) -> u32 {
+        self.cycle
+    }
+    pub fn cycles(&self) -> u32 {
+        self.cycles
+    }
+    pub fn block(&self) -> Block {
+        self.block
+    }
+    pub fn into_block(&self) -> &DecomposedTime {
+        &self.into_block
+    }
+    pub fn remaining(&self) -> &DecomposedTime {
+        &self.remaining
+    }
+}
+
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+                        Block::Work => "work",
+                        Block::Rest => "rest",
+                    })
+    }
+}
+
+impl fmt::Display for IntervalPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "cycle {} of {}, {} into {} block",
+               self.cycle,
+               self.cycles,
+               self.into_block.display_with(LeadingZeros::All).with_min_unit(Unit::Seconds),
+               self.block)
+    }
+}
+
+// Block offsets are below the sum of two `Duration`s, so they always fit.
+fn decompose_nanos(nanos: u128) -> DecomposedTime {
+    let secs = (nanos / 1_000_000_000) as u64;
+    DecomposedTime::from_secs_nanos(secs, (nanos % 1_000_000_000) as u32, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let scheme = IntervalScheme::new(Duration::from_secs(25 * 60),
+                                         Duration::from_secs(5 * 60),
+                                         8);
+        let elapsed = Duration::from_secs(2 * 30 * 60 + 12 * 60 + 30);
+        let position = scheme.position(elapsed).unwrap().unwrap();
+        assert_eq!(position.to_string(), "cycle 3 of 8, 12:30 into work block");
+        assert_eq!(position.remaining().to_string(), "12:30");
+
+        let position = scheme.position(Duration::from_secs(26 * 60)).unwrap().unwrap();
+        assert_eq!((position.cycle(), position.block()), (1, Block::Rest));
+        assert_eq!(position.to_string(), "cycle 1 of 8, 01:00 into rest block");
+
+        assert_eq!(scheme.position(scheme.total().unwrap()).unwrap(), None);
+        assert_eq!(IntervalScheme::pomodoro().total(), Some(Duration::from_secs(2 * 3600)));
+        let endless = IntervalScheme::new(Duration::from_secs(u64::MAX / 2),
+                                          Duration::from_secs(0),
+                                          3);
+        assert_eq!(endless.total(), None);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json;
+
+use decomposed::DecomposedTime;
+use units::Unit;
+
+// `DecomposedTime` as a JSON-style object: `{"sign":1,"years":0,"days":2,"hours":3,...}`.
+// With `omit_zero`, only the sign and the non-zero fields are written. Missing fields read back
+// as zero, and fields past their unit's range are carried into the larger units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Structured {
+    time: DecomposedTime,
+    omit_zero: bool,
+}
+
+impl Structured {
+    pub fn new(time: DecomposedTime) -> Structured {
+        Structured {
+            time,
+            omit_zero: false,
+        }
+    }
+    #[must_use]
+    pub fn with_omit_zero(mut self, omit_zero: bool) -> Structured {
+        self.omit_zero = omit_zero;
+        self
+    }
+    pub fn time(&self) -> &DecomposedTime {
+        &self.time
+    }
+    pub fn into_time(self) -> DecomposedTime {
+        self.time
+    }
+}
+
+impl DecomposedTime {
+    pub fn to_json(&self, omit_zero: bool) -> String {
+        let structured = Structured::new(self.clone()).with_omit_zero(omit_zero);
+        serde_json::to_string(&structured).expect("a structured duration always serializes")
+    }
+    pub fn from_json(json: &str) -> Result<DecomposedTime, serde_json::Error> {
+        serde_json::from_str::<Structured>(json).map(Structured::into_time)
+    }
+}
+
+impl Serialize for Structured {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        let fields = self.time.fields().filter(|&(_, value)| !self.omit_zero || value != 0);
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("sign", &self.time.signum())?;
+        for (unit, value) in fields {
+            map.serialize_entry(unit.name(), &value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Structured {
+    fn deserialize<D>(deserializer: D) -> Result<Structured, D::Error>
+        where D: Deserializer<'de>
+    {
+        deserializer.deserialize_map(StructuredVisitor)
+    }
+}
+
+struct StructuredVisitor;
+
+impl<'de> Visitor<'de> for StructuredVisitor {
+    type Value = Structured;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of duration fields")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Structured, A::Error>
+        where A: MapAccess<'de>
+    {
+        let mut builder = DecomposedTime::builder();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "sign" {
+                builder = match map.next_value::<i8>()? {
+                    1 => builder.negative(false),
+                    -1 => builder.negative(true),
+                    _ => return Err(de::Error::custom("sign must be 1 or -1")),
+                };
+                continue;
+            }
+
+            let unit = key.parse::<Unit>()
+                .ok()
+                .filter(|unit| unit.name() == key)
+                .ok_or_else(|| de::Error::unknown_field(&key, FIELDS))?;
+            let value = map.next_value::<u64>()?;
+            builder = match unit {
+                Unit::Years => builder.years(value),
+                Unit::Months => return Err(de::Error::unknown_field(&key, FIELDS)),
+                Unit::Days => builder.days(value),
+                Unit::Hours => builder.hours(value),
+                Unit::Minutes => builder.minutes(value),
+                Unit::Seconds => builder.seconds(value),
+                Unit::Milliseconds => builder.milliseconds(value),
+                Unit::Microseconds => builder.microseconds(value),
+                Unit::Nanoseconds => builder.nanoseconds(value),
+            };
+        }
+
+        builder.build()
+            .map(Structured::new)
+            .map_err(|_| de::Error::custom("duration out of range"))
+    }
+}
+
+const FIELDS: &[&str] = &["sign", "years", "days", "hours", "minutes", "seconds", "milliseconds",
+                          "microseconds", "nanoseconds"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let time = DecomposedTime::new(0, 2, 3, 0, 0, 0.5, false);
+        assert_eq!(time.to_json(true),
+                   r#"{"sign":-1,"days":2,"hours":3,"milliseconds":500}"#);
+        assert_eq!(time.to_json(false),
+                   concat!(r#"{"sign":-1,"years":0,"days":2,"hours":3,"minutes":0,"#,
+                           r#""seconds":0,"milliseconds":500,"microseconds":0,"nanoseconds":0}"#));
+        assert_eq!(DecomposedTime::from_json(&time.to_json(true)).unwrap(), time);
+        assert_eq!(DecomposedTime::from_json(&time.to_json(false)).unwrap(), time);
+
+        let carried = DecomposedTime::from_json(r#"{"minutes":90}"#).unwrap();
+        assert_eq!((carried.hours(), carried.minutes(), carried.is_positive()), (1, 30, true));
+
+        assert!(DecomposedTime::from_json(r#"{"sign":0}"#).is_err());
+        assert!(DecomposedTime::from_json(r#"{"hour":1}"#).is_err());
+        assert!(DecomposedTime::from_json(r#"{"weeks":1}"#).is_err());
+    }
+}
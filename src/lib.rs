@@ -5,5 +5,63 @@ extern crate float_duration;
 #[cfg(feature = "chrono")]
 extern crate chrono;
 
+#[cfg(feature = "fast-fmt")]
+extern crate itoa;
+#[cfg(feature = "fast-fmt")]
+extern crate ryu;
+
+#[cfg(feature = "rayon")]
+extern crate rayon;
+
+#[cfg(feature = "defmt")]
+extern crate defmt;
+
+#[cfg(feature = "tracing")]
+extern crate tracing as tracing_lib;
+
+#[cfg(feature = "rkyv")]
+extern crate rkyv;
+
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+
+#[cfg(feature = "prost")]
+extern crate prost_types;
+
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_test;
+#[cfg(test)]
+extern crate regex;
+
+#[cfg(feature = "verify")]
+#[cfg_attr(all(test, feature = "float_duration"), macro_use)]
+extern crate proptest;
+#[cfg(feature = "verify")]
+extern crate quickcheck;
+
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod calendar;
+#[cfg(feature = "serde")]
+pub mod config;
 pub mod decomposed;
+pub mod elapsed;
+pub mod env;
 pub mod fmt;
+pub mod humanize;
+pub mod interval;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod metrics;
+pub mod span;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+pub mod units;
+#[cfg(feature = "verify")]
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
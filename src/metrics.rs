@@ -0,0 +1,161 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use decomposed::{Decompose, DecomposedTime};
+use fmt::{format_range, FormatError, RangeOptions};
+use units::{Unit, NANOS_PER_SEC};
+
+// Prometheus' duration units, largest first; it treats a year as 365 days and has weeks.
+const PROMETHEUS_UNITS: [(&str, u128); 7] = [("y", 365 * 86_400_000),
+                                             ("w", 7 * 86_400_000),
+                                             ("d", 86_400_000),
+                                             ("h", 3_600_000),
+                                             ("m", 60_000),
+                                             ("s", 1_000),
+                                             ("ms", 1)];
+
+// A histogram bucket of a log-scale series: its position in the series and its label, the
+// bucket's bounds as `format_range` writes them ("1–10 ms").
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogBucket {
+    pub index: u32,
+    pub label: String,
+}
+
+// Renders the duration as plain float seconds ("90.5", "-0.000001") the way OpenTelemetry and
+// Prometheus expect histogram samples, using exact integer arithmetic rather than an f64.
+pub fn seconds<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = decompose(time)?;
+    let nanos = time.subsec_nanoseconds();
+
+    let mut out = String::new();
+    if time.is_negative() && (time.total_seconds() > 0 || nanos > 0) {
+        out.push('-');
+    }
+    write!(out, "{}", time.total_seconds()).map_err(|_| FormatError::FmtError)?;
+    if nanos > 0 {
+        let fraction = format!("{:09}", nanos);
+        out.push('.');
+        out.push_str(fraction.trim_end_matches('0'));
+    }
+    Ok(out)
+}
+
+// Renders the duration in Prometheus notation ("1h30m", "0s") for rule and config generation.
+// Prometheus durations are non-negative with millisecond resolution, so negative values are
+// rejected and anything below a millisecond is truncated.
+pub fn prometheus_duration<D>(time: D) -> Result<String, FormatError>
+    where D: Decompose
+{
+    let time = decompose(time)?;
+    let mut millis = time.total_nanoseconds().ok_or(FormatError::ValueOutOfRange)? /
+                     Unit::Milliseconds.nanoseconds() as u128;
+    if time.is_negative() && millis > 0 {
+        return Err(FormatError::ValueOutOfRange);
+    }
+    if millis == 0 {
+        return Ok("0s".to_string());
+    }
+
+    let mut out = String::new();
+    for &(unit, scale) in &PROMETHEUS_UNITS {
+        if millis >= scale {
+            write!(out, "{}{}", millis / scale, unit).map_err(|_| FormatError::FmtError)?;
+            millis %= scale;
+        }
+    }
+    Ok(out)
+}
+
+// The bucket of a log-scale histogram `time` falls in. Bucket `n` runs from `base`^`n`
+// nanoseconds up to `base`^(`n` + 1), except bucket 0, which starts at zero. Negative durations
+// and a `base` below 2 are `ValueOutOfRange`.
+pub fn log_bucket<D>(time: D, base: u32) -> Result<LogBucket, FormatError>
+    where D: Decompose
+{
+    if base < 2 {
+        return Err(FormatError::ValueOutOfRange);
+    }
+    let time = decompose(time)?;
+    let nanos = time.total_nanoseconds().ok_or(FormatError::ValueOutOfRange)?;
+    if time.is_negative() && nanos > 0 {
+        return Err(FormatError::ValueOutOfRange);
+    }
+
+    let base = base as u128;
+    let (mut index, mut lower) = (0, 1u128);
+    while matches!(lower.checked_mul(base), Some(next) if next <= nanos) {
+        index += 1;
+        lower *= base;
+    }
+    let lower = if index == 0 { 0 } else { lower };
+    let upper = if index == 0 { Some(base) } else { lower.checked_mul(base) };
+    let upper = upper.ok_or(FormatError::ValueOutOfRange)?;
+    let options = RangeOptions::new().with_precision(3);
+    Ok(LogBucket {
+           index,
+           label: format_range(duration_from_nanos(lower)?, duration_from_nanos(upper)?, &options)?,
+       })
+}
+
+fn duration_from_nanos(nanos: u128) -> Result<Duration, FormatError> {
+    let secs = nanos / NANOS_PER_SEC as u128;
+    if secs > u64::MAX as u128 {
+        return Err(FormatError::ValueOutOfRange);
+    }
+    Ok(Duration::new(secs as u64, (nanos % NANOS_PER_SEC as u128) as u32))
+}
+
+fn decompose<D>(time: D) -> Result<DecomposedTime, FormatError>
+    where D: Decompose
+{
+    time.decompose().map_err(|e| FormatError::DecomposeError(e.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_seconds() {
+        assert_eq!(seconds(Duration::new(90, 500_000_000)).unwrap(), "90.5");
+        assert_eq!(seconds(Duration::new(3, 0)).unwrap(), "3");
+        assert_eq!(seconds(Duration::new(0, 1_000)).unwrap(), "0.000001");
+        assert_eq!(seconds(Duration::new(0, 0)).unwrap(), "0");
+        assert_eq!(seconds(DecomposedTime::new(0, 0, 0, 0, 2, 0.25, false)).unwrap(),
+                   "-2.25");
+    }
+
+    #[test]
+    fn test_prometheus_duration() {
+        assert_eq!(prometheus_duration(Duration::from_secs(5400)).unwrap(), "1h30m");
+        assert_eq!(prometheus_duration(Duration::from_secs(8 * 86_400 + 1)).unwrap(),
+                   "1w1d1s");
+        assert_eq!(prometheus_duration(Duration::new(1, 250_999_999)).unwrap(), "1s250ms");
+        assert_eq!(prometheus_duration(Duration::new(0, 999)).unwrap(), "0s");
+        assert_eq!(prometheus_duration(DecomposedTime::new(1, 0, 0, 0, 0, 0.0, true)).unwrap(),
+                   "1y");
+        assert_eq!(prometheus_duration(DecomposedTime::new(0, 0, 0, 1, 0, 0.0, false)),
+                   Err(FormatError::ValueOutOfRange));
+    }
+
+    #[test]
+    fn test_log_bucket() {
+        let bucket = |time: Duration, base: u32| log_bucket(time, base).unwrap();
+        assert_eq!(bucket(Duration::from_micros(3_500), 10),
+                   LogBucket {
+                       index: 6,
+                       label: "1\u{2013}10 ms".to_owned(),
+                   });
+        assert_eq!(bucket(Duration::from_millis(1), 10).index, 6);
+        assert_eq!(bucket(Duration::from_millis(250), 10).label, "100 ms\u{2013}1 s");
+        assert_eq!(bucket(Duration::new(0, 0), 10).label, "0\u{2013}10 ns");
+        assert_eq!(bucket(Duration::new(0, 700), 2).label, "512 ns\u{2013}1.024 µs");
+        assert_eq!(log_bucket(DecomposedTime::new(0, 0, 0, 0, 1, 0.0, false), 10),
+                   Err(FormatError::ValueOutOfRange));
+        assert_eq!(log_bucket(Duration::from_secs(1), 1), Err(FormatError::ValueOutOfRange));
+    }
+}
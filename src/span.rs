@@ -0,0 +1,134 @@
+use std::fmt;
+
+#[cfg(feature = "chrono")]
+use chrono::{self, DateTime, NaiveTime, TimeZone, Timelike};
+
+use decomposed::{Decompose, DecomposedTime};
+use fmt::FormatError;
+use units::Unit;
+
+// A duration rendered for schedules: "for 2h 15m", or "for 2h 15m (09:00–11:15)" once the
+// endpoints are known.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    time: DecomposedTime,
+    #[cfg(feature = "chrono")]
+    endpoints: Option<(NaiveTime, NaiveTime)>,
+}
+
+impl Span {
+    pub fn new(time: DecomposedTime) -> Span {
+        Span {
+            time,
+            #[cfg(feature = "chrono")]
+            endpoints: None,
+        }
+    }
+    pub fn from_duration<D>(time: D) -> Result<Span, FormatError>
+        where D: Decompose
+    {
+        time.decompose().map(Span::new).map_err(|e| FormatError::DecomposeError(e.into()))
+    }
+    #[cfg(feature = "chrono")]
+    pub fn between<Tz>(start: &DateTime<Tz>, end: &DateTime<Tz>) -> Result<Span, FormatError>
+        where Tz: TimeZone
+    {
+        let delta = end.clone().signed_duration_since(start.clone());
+        if delta < chrono::TimeDelta::zero() {
+            return Err(FormatError::ValueOutOfRange);
+        }
+        Ok(Span::from_duration(delta)?.with_endpoints(start.time(), end.time()))
+    }
+
+    pub fn time(&self) -> &DecomposedTime {
+        &self.time
+    }
+    #[cfg(feature = "chrono")]
+    pub fn endpoints(&self) -> Option<(NaiveTime, NaiveTime)> {
+        self.endpoints
+    }
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn with_endpoints(mut self, start: NaiveTime, end: NaiveTime) -> Span {
+        self.endpoints = Some((start, end));
+        self
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("for ")?;
+        if self.time.is_negative() && self.time.total_seconds() > 0 {
+            f.write_str("-")?;
+        }
+
+        let mut written = false;
+        for (unit, value) in self.time.fields().filter(|&(unit, _)| unit >= Unit::Seconds) {
+            if value > 0 {
+                if written {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{}{}", value, unit.symbol())?;
+                written = true;
+            }
+        }
+        if !written {
+            f.write_str("0s")?;
+        }
+
+        #[cfg(feature = "chrono")]
+        {
+            if let Some((start, end)) = self.endpoints {
+                f.write_str(" (")?;
+                write_time_of_day(f, start)?;
+                f.write_str("–")?;
+                write_time_of_day(f, end)?;
+                f.write_str(")")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "chrono")]
+fn write_time_of_day(f: &mut fmt::Formatter, time: NaiveTime) -> fmt::Result {
+    write!(f, "{:02}:{:02}", time.hour(), time.minute())?;
+    if time.second() != 0 {
+        write!(f, ":{:02}", time.second())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_span() {
+        assert_eq!(Span::from_duration(Duration::from_secs(8100)).unwrap().to_string(),
+                   "for 2h 15m");
+        assert_eq!(Span::from_duration(Duration::from_secs(86_405)).unwrap().to_string(),
+                   "for 1d 5s");
+        assert_eq!(Span::from_duration(Duration::from_millis(300)).unwrap().to_string(),
+                   "for 0s");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_span_endpoints() {
+        use chrono::Utc;
+
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 3, 1, 11, 15, 0).unwrap();
+        assert_eq!(Span::between(&start, &end).unwrap().to_string(),
+                   "for 2h 15m (09:00–11:15)");
+        assert_eq!(Span::between(&end, &start), Err(FormatError::ValueOutOfRange));
+
+        let span = Span::from_duration(Duration::from_secs(30))
+            .unwrap()
+            .with_endpoints(NaiveTime::from_hms_opt(23, 59, 45).unwrap(),
+                            NaiveTime::from_hms_opt(0, 0, 15).unwrap());
+        assert_eq!(span.to_string(), "for 30s (23:59:45–00:00:15)");
+    }
+}
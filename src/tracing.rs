@@ -0,0 +1,58 @@
+use std::fmt;
+
+use tracing_lib::field::{self, DisplayValue};
+use tracing_lib::Span;
+
+use decomposed::{Decompose, DecomposedTime};
+use fmt::SiDuration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Human(Option<SiDuration>);
+
+// Records durations on spans and events as `elapsed = %duration_fmt::tracing::display(d)`,
+// keeping the raw value alongside under an `_ns` suffix (`elapsed_ns = d.as_nanos()`) when it
+// needs to stay machine-readable. To index on the individual fields instead, declare them on
+// the span (`hours = field::Empty`, ...) and fill them in with `record_fields`.
+pub fn display<D>(time: D) -> DisplayValue<Human>
+    where D: Decompose
+{
+    field::display(Human(time.decompose().ok().map(SiDuration::new)))
+}
+
+// Records the `DecomposedTime::as_kv` pairs on `span`. tracing ignores names the span didn't
+// declare, so callers can pick just the fields they index on.
+pub fn record_fields(span: &Span, time: &DecomposedTime) {
+    for &(name, value) in time.as_kv().iter() {
+        span.record(name, value);
+    }
+}
+
+impl fmt::Display for Human {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(ref si) => fmt::Display::fmt(si, f),
+            None => f.write_str("<invalid duration>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "float_duration")]
+    use float_duration::FloatDuration;
+    use std::time::Duration;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", display(Duration::from_micros(3200))), "3.2ms");
+        assert_eq!(format!("{:?}", display(Duration::from_millis(1500))), "1.5s");
+    }
+
+    #[cfg(feature = "float_duration")]
+    #[test]
+    fn test_display_invalid() {
+        assert_eq!(format!("{}", display(FloatDuration::seconds(f64::NAN))),
+                   "<invalid duration>");
+    }
+}
@@ -0,0 +1,202 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use fmt::FormatError;
+
+pub const SECS_PER_MINUTE: u64 = 60;
+pub const SECS_PER_HOUR: u64 = 60 * SECS_PER_MINUTE;
+pub const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+pub const SECS_PER_WEEK: u64 = 7 * SECS_PER_DAY;
+// The year of `Unit::Years` and every decomposition in the crate: 365 days, no leap days.
+pub const SECS_PER_YEAR: u64 = 365 * SECS_PER_DAY;
+// 365.25 days, the year of light-years and of most astronomical conversions.
+pub const SECS_PER_JULIAN_YEAR: u64 = 31_557_600;
+// 365.2425 days, the average year of the Gregorian calendar.
+pub const SECS_PER_GREGORIAN_YEAR: u64 = 31_556_952;
+
+pub const MILLIS_PER_SEC: u64 = 1_000;
+pub const MICROS_PER_SEC: u64 = 1_000_000;
+pub const NANOS_PER_MICRO: u64 = 1_000;
+pub const NANOS_PER_MILLI: u64 = 1_000_000;
+pub const NANOS_PER_SEC: u64 = 1_000_000_000;
+pub const NANOS_PER_MINUTE: u64 = SECS_PER_MINUTE * NANOS_PER_SEC;
+pub const NANOS_PER_HOUR: u64 = SECS_PER_HOUR * NANOS_PER_SEC;
+pub const NANOS_PER_DAY: u64 = SECS_PER_DAY * NANOS_PER_SEC;
+pub const NANOS_PER_WEEK: u64 = SECS_PER_WEEK * NANOS_PER_SEC;
+// 30.5 days, the month of the humanize formatters and `Abbreviations::parse`.
+pub const NANOS_PER_MONTH: u64 = NANOS_PER_DAY * 61 / 2;
+pub const NANOS_PER_YEAR: u64 = SECS_PER_YEAR * NANOS_PER_SEC;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Years,
+    // Only the humanize formatters count months; a `DecomposedTime` has no months field, so
+    // `ALL` leaves them out.
+    Months,
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl Unit {
+    // Every unit a `DecomposedTime` is split into, most significant first.
+    pub const ALL: [Unit; 8] = [Unit::Years,
+                                Unit::Days,
+                                Unit::Hours,
+                                Unit::Minutes,
+                                Unit::Seconds,
+                                Unit::Milliseconds,
+                                Unit::Microseconds,
+                                Unit::Nanoseconds];
+
+    pub const fn nanoseconds(self) -> u64 {
+        match self {
+            Unit::Years => NANOS_PER_YEAR,
+            Unit::Months => NANOS_PER_MONTH,
+            Unit::Days => NANOS_PER_DAY,
+            Unit::Hours => NANOS_PER_HOUR,
+            Unit::Minutes => NANOS_PER_MINUTE,
+            Unit::Seconds => NANOS_PER_SEC,
+            Unit::Milliseconds => NANOS_PER_MILLI,
+            Unit::Microseconds => NANOS_PER_MICRO,
+            Unit::Nanoseconds => 1,
+        }
+    }
+    pub fn seconds(self) -> f64 {
+        self.nanoseconds() as f64 / 1.0e9
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Years => "years",
+            Unit::Months => "months",
+            Unit::Days => "days",
+            Unit::Hours => "hours",
+            Unit::Minutes => "minutes",
+            Unit::Seconds => "seconds",
+            Unit::Milliseconds => "milliseconds",
+            Unit::Microseconds => "microseconds",
+            Unit::Nanoseconds => "nanoseconds",
+        }
+    }
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Years => "y",
+            Unit::Months => "mo",
+            Unit::Days => "d",
+            Unit::Hours => "h",
+            Unit::Minutes => "m",
+            Unit::Seconds => "s",
+            Unit::Milliseconds => "ms",
+            Unit::Microseconds => "µs",
+            Unit::Nanoseconds => "ns",
+        }
+    }
+}
+
+// Units order by magnitude, so `Unit::Years > Unit::Seconds`.
+impl Ord for Unit {
+    fn cmp(&self, other: &Unit) -> Ordering {
+        self.nanoseconds().cmp(&other.nanoseconds())
+    }
+}
+
+impl PartialOrd for Unit {
+    fn partial_cmp(&self, other: &Unit) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Splits nanoseconds below a second into milliseconds, microseconds and nanoseconds, the
+// subsecond fields of a `DecomposedTime`.
+pub const fn split_subsec_nanos(subsec_nanos: u32) -> (u32, u32, u32) {
+    (subsec_nanos / NANOS_PER_MILLI as u32,
+     subsec_nanos / NANOS_PER_MICRO as u32 % 1_000,
+     subsec_nanos % NANOS_PER_MICRO as u32)
+}
+
+// Splits whole seconds into 365-day years, days, hours, minutes and seconds, or `None` if the
+// years don't fit in a u64.
+pub fn split_secs(secs: u128) -> Option<(u64, u32, u32, u32, u32)> {
+    let (year, day) = (SECS_PER_YEAR as u128, SECS_PER_DAY as u128);
+    let (hour, minute) = (SECS_PER_HOUR as u128, SECS_PER_MINUTE as u128);
+
+    let years = secs / year;
+    if years > u64::MAX as u128 {
+        return None;
+    }
+    Some((years as u64,
+          (secs % year / day) as u32,
+          (secs % day / hour) as u32,
+          (secs % hour / minute) as u32,
+          (secs % minute) as u32))
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Unit {
+    type Err = FormatError;
+
+    // Accepts the plural name, its singular form, or the symbol ("us" for microseconds too), in
+    // any case.
+    fn from_str(s: &str) -> Result<Unit, FormatError> {
+        let name = s.trim().to_lowercase();
+        let name = name.as_str();
+        Unit::ALL
+            .iter()
+            .chain(&[Unit::Months])
+            .cloned()
+            .find(|unit| {
+                name == unit.name() || name == unit.symbol() ||
+                name == &unit.name()[..unit.name().len() - 1]
+            })
+            .or(if name == "us" { Some(Unit::Microseconds) } else { None })
+            .ok_or(FormatError::UnknownField)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit() {
+        assert_eq!(Unit::Years.nanoseconds(), 31_536_000_000_000_000);
+        assert_eq!(Unit::Hours.seconds(), 3600.0);
+        assert!(Unit::Years > Unit::Days && Unit::Microseconds > Unit::Nanoseconds);
+
+        let mut sorted = Unit::ALL;
+        sorted.sort();
+        sorted.reverse();
+        assert_eq!(sorted, Unit::ALL);
+
+        assert_eq!(Unit::Milliseconds.to_string(), "milliseconds");
+        assert_eq!("hour".parse(), Ok(Unit::Hours));
+        assert_eq!("us".parse(), Ok(Unit::Microseconds));
+        assert_eq!("month".parse(), Ok(Unit::Months));
+        assert!(Unit::Years > Unit::Months && Unit::Months > Unit::Days);
+        assert_eq!("ms".parse(), Ok(Unit::Milliseconds));
+        assert_eq!("\u{a0}Hours".parse(), Ok(Unit::Hours));
+        assert_eq!("fortnights".parse::<Unit>(), Err(FormatError::UnknownField));
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(SECS_PER_WEEK, 604_800);
+        assert_eq!(SECS_PER_JULIAN_YEAR, 36_525 * SECS_PER_DAY / 100);
+        assert_eq!(SECS_PER_GREGORIAN_YEAR, 3_652_425 * SECS_PER_DAY / 10_000);
+        assert_eq!(Unit::Years.nanoseconds(), SECS_PER_YEAR * NANOS_PER_SEC);
+
+        assert_eq!(split_subsec_nanos(123_456_789), (123, 456, 789));
+        assert_eq!(split_secs(SECS_PER_YEAR as u128 + 90_061), Some((1, 1, 1, 1, 1)));
+        assert_eq!(split_secs(u128::MAX), None);
+    }
+}
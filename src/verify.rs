@@ -0,0 +1,86 @@
+use proptest::arbitrary;
+use proptest::prelude::{any, BoxedStrategy, Strategy};
+use quickcheck::{Arbitrary, Gen};
+
+use decomposed::DecomposedTime;
+use units;
+
+// Years are bounded so that generated values survive a round trip through f64 seconds.
+const MAX_YEARS: u64 = 10_000;
+
+fn from_parts(is_positive: bool,
+              years: u64,
+              days: u32,
+              hours: u32,
+              minutes: u32,
+              seconds: u32,
+              nanos: u32)
+              -> DecomposedTime {
+    DecomposedTime::new(years,
+                        days,
+                        hours,
+                        minutes,
+                        seconds,
+                        nanos as f64 / units::NANOS_PER_SEC as f64,
+                        is_positive)
+}
+
+impl arbitrary::Arbitrary for DecomposedTime {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<DecomposedTime>;
+
+    fn arbitrary_with(_: ()) -> BoxedStrategy<DecomposedTime> {
+        (any::<bool>(),
+         0..MAX_YEARS,
+         0..365u32,
+         0..24u32,
+         0..60u32,
+         0..60u32,
+         0..units::NANOS_PER_SEC as u32)
+                .prop_map(|(is_positive, years, days, hours, minutes, seconds, nanos)| {
+                              from_parts(is_positive, years, days, hours, minutes, seconds, nanos)
+                          })
+                .boxed()
+    }
+}
+
+impl Arbitrary for DecomposedTime {
+    fn arbitrary(g: &mut Gen) -> DecomposedTime {
+        from_parts(bool::arbitrary(g),
+                   u64::arbitrary(g) % MAX_YEARS,
+                   u32::arbitrary(g) % 365,
+                   u32::arbitrary(g) % 24,
+                   u32::arbitrary(g) % 60,
+                   u32::arbitrary(g) % 60,
+                   u32::arbitrary(g) % units::NANOS_PER_SEC as u32)
+    }
+}
+
+// The property tests round-trip through `FloatDuration`.
+#[cfg(all(test, feature = "float_duration"))]
+mod tests {
+    use super::*;
+    use decomposed::Decompose;
+    use float_duration::FloatDuration;
+    use quickcheck::quickcheck;
+
+    fn round_trips(time: DecomposedTime) -> bool {
+        let secs = FloatDuration::from(time).as_seconds();
+        let back = FloatDuration::seconds(secs).decompose().unwrap();
+        let back_secs = FloatDuration::from(back).as_seconds();
+
+        (back_secs - secs).abs() <= 1.0e-6 * secs.abs().max(1.0)
+    }
+
+    proptest! {
+        #[test]
+        fn test_float_round_trip(time in any::<DecomposedTime>()) {
+            prop_assert!(round_trips(time));
+        }
+    }
+
+    #[test]
+    fn test_float_round_trip_quickcheck() {
+        quickcheck(round_trips as fn(DecomposedTime) -> bool);
+    }
+}
@@ -0,0 +1,45 @@
+use js_sys::{Object, Reflect};
+
+use decomposed::DecomposedTime;
+
+// A JavaScript duration in milliseconds, as `Date.now() - start` or `performance.now()` deltas
+// give it. Plain `f64`s don't decompose, since the unit would be a guess.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Millis(pub f64);
+
+// Intl.DurationFormat requires every field of a duration record to share the same sign.
+pub fn intl_duration_fields(time: &DecomposedTime) -> [(&'static str, f64); 8] {
+    let sign = if time.is_negative() { -1.0 } else { 1.0 };
+    [("years", sign * time.years() as f64),
+     ("days", sign * time.days() as f64),
+     ("hours", sign * time.hours() as f64),
+     ("minutes", sign * time.minutes() as f64),
+     ("seconds", sign * time.seconds() as f64),
+     ("milliseconds", sign * time.milliseconds() as f64),
+     ("microseconds", sign * time.microseconds() as f64),
+     ("nanoseconds", sign * time.nanoseconds() as f64)]
+}
+
+pub fn to_intl_duration(time: &DecomposedTime) -> Object {
+    let record = Object::new();
+    for &(name, value) in intl_duration_fields(time).iter() {
+        Reflect::set(&record, &name.into(), &value.into())
+            .expect("setting a property on a plain object cannot fail");
+    }
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decomposed::Decompose;
+
+    #[test]
+    fn test_intl_fields() {
+        let fields = intl_duration_fields(&Millis(-3_723_500.0).decompose().unwrap());
+        assert_eq!(fields[2], ("hours", -1.0));
+        assert_eq!(fields[3], ("minutes", -2.0));
+        assert_eq!(fields[4], ("seconds", -3.0));
+        assert_eq!(fields[5], ("milliseconds", -500.0));
+    }
+}